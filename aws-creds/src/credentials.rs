@@ -2,10 +2,16 @@
 
 use anyhow::anyhow;
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac, NewMac};
 use ini::Ini;
 use serde_xml_rs as serde_xml;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 use url::Url;
 
 /// AWS access credentials: access key, secret key, and optional token.
@@ -34,8 +40,8 @@ use url::Url;
 ///
 /// Credentials may also be initialized directly or by the following environment variables:
 ///
-///   - `AWS_ACCESS_KEY_ID`,
-///   - `AWS_SECRET_ACCESS_KEY`
+///   - `AWS_ACCESS_KEY_ID`, or its older alias `AWS_ACCESS_KEY`
+///   - `AWS_SECRET_ACCESS_KEY`, or its older alias `AWS_SECRET_KEY`
 ///   - `AWS_SESSION_TOKEN`
 ///
 /// The order of preference is arguments, then environment, and finally AWS
@@ -64,6 +70,39 @@ pub struct Credentials {
     /// Temporary token issued by AWS service.
     pub security_token: Option<String>,
     pub session_token: Option<String>,
+    /// When temporary credentials expire, `None` for long-lived credentials, see `is_expired`.
+    pub expiration: Option<DateTime<Utc>>,
+}
+
+/// Credentials are considered expired this many seconds before their expiration, so that they
+/// are not used for requests that would reach S3 after it.
+pub const EXPIRATION_BUFFER_SECS: i64 = 300;
+
+/// Endpoint of the EC2 instance metadata service, unless `AWS_EC2_METADATA_SERVICE_ENDPOINT` is
+/// set.
+const IMDS_ENDPOINT: &str = "http://169.254.169.254";
+/// Lifetime of IMDSv2 session tokens, in seconds.
+const IMDS_TOKEN_TTL: &str = "21600";
+const IMDS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Global STS endpoint, requests to it are signed for `STS_REGION`.
+const STS_ENDPOINT: &str = "https://sts.amazonaws.com/";
+const STS_REGION: &str = "us-east-1";
+
+#[derive(Deserialize, Debug)]
+pub struct AssumeRoleResponse {
+    #[serde(rename = "AssumeRoleResult")]
+    pub assume_role_result: AssumeRoleResult,
+    #[serde(rename = "ResponseMetadata")]
+    pub response_metadata: ResponseMetadata,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AssumeRoleResult {
+    #[serde(rename = "AssumedRoleUser")]
+    pub assumed_role_user: AssumedRoleUser,
+    #[serde(rename = "Credentials")]
+    pub credentials: StsResponseCredentials,
 }
 
 #[derive(Deserialize, Debug)]
@@ -162,9 +201,51 @@ impl Credentials {
                     .credentials
                     .session_token,
             ),
+            expiration: parse_expiration(
+                &serde_response
+                    .assume_role_with_web_identity_result
+                    .credentials
+                    .expiration,
+            ),
         })
     }
 
+    /// Temporary credentials of the role `role_arn`, from an STS `AssumeRole` request signed
+    /// with `base`. The session token is returned along with the keys, and the credentials
+    /// expire, see `expiration`, after which the role has to be assumed again.
+    ///
+    /// ```no_run
+    /// use awscreds::Credentials;
+    ///
+    /// let base = Credentials::default().unwrap();
+    /// let credentials = Credentials::from_assume_role(
+    ///     "arn:aws:iam::123456789012:role/s3-reader",
+    ///     "rust-s3",
+    ///     &base,
+    /// )
+    /// .unwrap();
+    /// println!("Valid until {:?}", credentials.expiration);
+    /// ```
+    pub fn from_assume_role(
+        role_arn: &str,
+        session_name: &str,
+        base: &Credentials,
+    ) -> Result<Credentials> {
+        assume_role(STS_ENDPOINT, STS_REGION, role_arn, session_name, base)
+    }
+
+    /// Whether the credentials expire within `EXPIRATION_BUFFER_SECS`, or already have.
+    /// Credentials without an expiration, like keys given directly or read from the environment
+    /// or a profile, never expire.
+    pub fn is_expired(&self) -> bool {
+        match self.expiration {
+            Some(expiration) => {
+                expiration - chrono::Duration::seconds(EXPIRATION_BUFFER_SECS) <= Utc::now()
+            }
+            None => false,
+        }
+    }
+
     pub fn default() -> Result<Credentials> {
         Credentials::new(None, None, None, None, None)
     }
@@ -175,6 +256,7 @@ impl Credentials {
             secret_key: None,
             security_token: None,
             session_token: None,
+            expiration: None,
         })
     }
 
@@ -193,6 +275,7 @@ impl Credentials {
                 secret_key: secret_key.map(|s| s.to_string()),
                 security_token: security_token.map(|s| s.to_string()),
                 session_token: session_token.map(|s| s.to_string()),
+                expiration: None,
             });
         }
 
@@ -208,8 +291,10 @@ impl Credentials {
         security_token_var: Option<&str>,
         session_token_var: Option<&str>,
     ) -> Result<Credentials> {
-        let access_key = from_env_with_default(access_key_var, "AWS_ACCESS_KEY_ID")?;
-        let secret_key = from_env_with_default(secret_key_var, "AWS_SECRET_ACCESS_KEY")?;
+        let access_key =
+            from_env_with_alias(access_key_var, "AWS_ACCESS_KEY_ID", "AWS_ACCESS_KEY")?;
+        let secret_key =
+            from_env_with_alias(secret_key_var, "AWS_SECRET_ACCESS_KEY", "AWS_SECRET_KEY")?;
 
         let security_token = from_env_with_default(security_token_var, "AWS_SECURITY_TOKEN").ok();
         let session_token = from_env_with_default(session_token_var, "AWS_SESSION_TOKEN").ok();
@@ -218,6 +303,7 @@ impl Credentials {
             secret_key: Some(secret_key),
             security_token,
             session_token,
+            expiration: None,
         })
     }
 
@@ -225,42 +311,48 @@ impl Credentials {
         Credentials::from_env_specific(None, None, None, None)
     }
 
+    /// Temporary credentials of the IAM role attached to the EC2 instance, from the instance
+    /// metadata service, or of the ECS task when `AWS_CONTAINER_CREDENTIALS_RELATIVE_URI` is set.
+    ///
+    /// IMDSv2 is used, with a session token, falling back to IMDSv1 when no token can be had.
+    /// The metadata service endpoint can be overridden with `AWS_EC2_METADATA_SERVICE_ENDPOINT`,
+    /// otherwise it is only queried on EC2 instances. The credentials expire, see `expiration`.
     pub fn from_instance_metadata() -> Result<Credentials> {
-        if !Credentials::is_ec2() {
-            return Err(anyhow!("Not an EC2 instance"));
-        }
-        let mut resp: HashMap<String, String> =
-            match env::var("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI") {
-                Ok(credentials_path) => Some(
-                    attohttpc::get(&format!("http://169.254.170.2{}", credentials_path))
-                        .send()?
-                        .json()?,
-                ),
-                Err(_) => {
-                    let role = attohttpc::get(
-                        "http://169.254.169.254/latest/meta-data/iam/security-credentials",
-                    )
+        match env::var("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI") {
+            Ok(credentials_path) => Ok(Credentials::from_metadata_response(
+                attohttpc::get(&format!("http://169.254.170.2{}", credentials_path))
+                    .timeout(IMDS_TIMEOUT)
                     .send()?
-                    .text()?;
-
-                    let creds = attohttpc::get(&format!(
-                        "http://169.254.169.254/latest/meta-data/iam/security-credentials/{}",
-                        role
-                    ))
-                    .send()?
-                    .json()?;
-
-                    Some(creds)
-                }
+                    .json()?,
+            )),
+            Err(_) => {
+                let endpoint = match env::var("AWS_EC2_METADATA_SERVICE_ENDPOINT") {
+                    Ok(endpoint) => endpoint,
+                    Err(_) if Credentials::is_ec2() => IMDS_ENDPOINT.to_string(),
+                    Err(_) => return Err(anyhow!("Not an EC2 instance")),
+                };
+                Credentials::from_instance_metadata_endpoint(&endpoint)
             }
-            .unwrap();
+        }
+    }
 
-        Ok(Credentials {
+    /// Like `from_instance_metadata`, from the EC2 metadata service at `endpoint`.
+    pub(crate) fn from_instance_metadata_endpoint(endpoint: &str) -> Result<Credentials> {
+        Ok(Credentials::from_metadata_response(
+            instance_metadata_credentials(endpoint.trim_end_matches('/'))?,
+        ))
+    }
+
+    fn from_metadata_response(mut resp: HashMap<String, String>) -> Credentials {
+        Credentials {
             access_key: resp.remove("AccessKeyId"),
             secret_key: resp.remove("SecretAccessKey"),
             security_token: resp.remove("Token"),
             session_token: None,
-        })
+            expiration: resp
+                .remove("Expiration")
+                .and_then(|expiration| parse_expiration(&expiration)),
+        }
     }
 
     fn is_ec2() -> bool {
@@ -277,10 +369,19 @@ impl Credentials {
         false
     }
 
+    /// Load credentials for the given profile, defaulting to "default", from the file named by
+    /// `AWS_SHARED_CREDENTIALS_FILE`, or `~/.aws/credentials` if that is not set.
     pub fn from_profile(section: Option<&str>) -> Result<Credentials> {
-        let home_dir = dirs::home_dir().ok_or_else(|| anyhow!("Invalid home dir"))?;
-        let profile = format!("{}/.aws/credentials", home_dir.display());
-        let conf = Ini::load_from_file(&profile)?;
+        Credentials::from_profile_file(profile_path()?, section)
+    }
+
+    /// Load credentials for the given profile, defaulting to "default", from a credentials
+    /// file at an explicit path.
+    pub fn from_profile_file<P: AsRef<Path>>(
+        path: P,
+        section: Option<&str>,
+    ) -> Result<Credentials> {
+        let conf = Ini::load_from_file(path.as_ref())?;
         let section = section.unwrap_or("default");
         let data = conf
             .section(Some(section))
@@ -298,14 +399,338 @@ impl Credentials {
             secret_key: Some(secret_key),
             security_token: data.get("aws_security_token").map(|s| s.to_string()),
             session_token: data.get("aws_session_token").map(|s| s.to_string()),
+            expiration: None,
         };
         Ok(credentials)
     }
 }
 
+/// Expiration timestamp of temporary credentials, like `2024-06-01T12:00:00Z`.
+fn parse_expiration(expiration: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(expiration)
+        .ok()
+        .map(|expiration| expiration.with_timezone(&Utc))
+}
+
+/// `AssumeRole` request to the STS `endpoint`, signed for `region`.
+pub(crate) fn assume_role(
+    endpoint: &str,
+    region: &str,
+    role_arn: &str,
+    session_name: &str,
+    base: &Credentials,
+) -> Result<Credentials> {
+    let url = Url::parse(endpoint)?;
+    let host = match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        (Some(host), None) => host.to_string(),
+        (None, _) => return Err(anyhow!("STS endpoint {} has no host", endpoint)),
+    };
+    let body = url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("Action", "AssumeRole")
+        .append_pair("RoleArn", role_arn)
+        .append_pair("RoleSessionName", session_name)
+        .append_pair("Version", "2011-06-15")
+        .finish();
+    let headers = sts_headers(&host, url.path(), region, &body, base, &Utc::now())?;
+
+    let mut request = attohttpc::post(url.as_str());
+    for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+        request = request.header(*name, value.as_str());
+    }
+    let response = request.text(body).send()?;
+    let status = response.status();
+    let text = response.text()?;
+    if !status.is_success() {
+        return Err(anyhow!("AssumeRole failed with {}\n{}", status, text));
+    }
+
+    let credentials = serde_xml::from_str::<AssumeRoleResponse>(&text)?
+        .assume_role_result
+        .credentials;
+    Ok(Credentials {
+        access_key: Some(credentials.access_key_id),
+        secret_key: Some(credentials.secret_access_key),
+        security_token: None,
+        session_token: Some(credentials.session_token),
+        expiration: parse_expiration(&credentials.expiration),
+    })
+}
+
+/// Headers of a form encoded STS POST request with `body`, including the V4 `authorization`
+/// signed with `base`, sorted by name.
+pub(crate) fn sts_headers(
+    host: &str,
+    path: &str,
+    region: &str,
+    body: &str,
+    base: &Credentials,
+    datetime: &DateTime<Utc>,
+) -> Result<Vec<(&'static str, String)>> {
+    let request = QueryRequest {
+        method: "POST",
+        host,
+        path,
+        query: "",
+        body,
+        region,
+        service: "sts",
+    };
+    signed_headers(&request, base, datetime)
+}
+
+/// Request to an AWS query API, like STS or IAM, with its parameters in a form encoded body or
+/// in the query string.
+pub(crate) struct QueryRequest<'a> {
+    pub method: &'a str,
+    pub host: &'a str,
+    pub path: &'a str,
+    /// Canonical query string, with the parameters encoded and sorted by name.
+    pub query: &'a str,
+    pub body: &'a str,
+    pub region: &'a str,
+    pub service: &'a str,
+}
+
+/// Headers of `request`, including the V4 `authorization` signed with `base`, sorted by name.
+pub(crate) fn signed_headers(
+    request: &QueryRequest,
+    base: &Credentials,
+    datetime: &DateTime<Utc>,
+) -> Result<Vec<(&'static str, String)>> {
+    let (access_key, secret_key) = match (&base.access_key, &base.secret_key) {
+        (Some(access_key), Some(secret_key)) => (access_key, secret_key),
+        _ => {
+            return Err(anyhow!(
+                "Signing STS requests requires an access and secret key"
+            ))
+        }
+    };
+    let date = datetime.format("%Y%m%d").to_string();
+    let amz_date = datetime.format("%Y%m%dT%H%M%SZ").to_string();
+
+    let mut headers = vec![
+        (
+            "content-type",
+            "application/x-www-form-urlencoded; charset=utf-8".to_string(),
+        ),
+        ("host", request.host.to_string()),
+        ("x-amz-date", amz_date.clone()),
+    ];
+    if let Some(token) = base.session_token.as_ref().or(base.security_token.as_ref()) {
+        headers.push(("x-amz-security-token", token.clone()));
+    }
+
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        request.method,
+        request.path,
+        request.query,
+        canonical_headers,
+        signed_headers,
+        hex::encode(Sha256::digest(request.body.as_bytes()))
+    );
+    let scope = format!(
+        "{}/{}/{}/aws4_request",
+        date, request.region, request.service
+    );
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let mut key = format!("AWS4{}", secret_key).into_bytes();
+    for part in [
+        date.as_str(),
+        request.region,
+        request.service,
+        "aws4_request",
+    ]
+    .iter()
+    {
+        key = hmac_sha256(&key, part)?;
+    }
+    let signature = hex::encode(hmac_sha256(&key, &string_to_sign)?);
+
+    headers.insert(
+        0,
+        (
+            "authorization",
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                access_key, scope, signed_headers, signature
+            ),
+        ),
+    );
+    Ok(headers)
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Result<Vec<u8>> {
+    let mut mac = Hmac::<Sha256>::new_varkey(key).map_err(|e| anyhow!("{}", e))?;
+    mac.update(data.as_bytes());
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+/// Credentials document of the first IAM role listed by the instance metadata service at
+/// `endpoint`.
+fn instance_metadata_credentials(endpoint: &str) -> Result<HashMap<String, String>> {
+    let token = attohttpc::put(format!("{}/latest/api/token", endpoint))
+        .header("X-aws-ec2-metadata-token-ttl-seconds", IMDS_TOKEN_TTL)
+        .timeout(IMDS_TIMEOUT)
+        .send()
+        .ok()
+        .filter(|response| response.is_success())
+        .and_then(|response| response.text().ok());
+
+    let get = |role: &str| -> Result<attohttpc::Response> {
+        let mut request = attohttpc::get(format!(
+            "{}/latest/meta-data/iam/security-credentials/{}",
+            endpoint, role
+        ))
+        .timeout(IMDS_TIMEOUT);
+        if let Some(token) = &token {
+            request = request.header("X-aws-ec2-metadata-token", token.as_str());
+        }
+        let response = request.send()?;
+        if !response.is_success() {
+            return Err(anyhow!(
+                "Instance metadata request failed with {}",
+                response.status()
+            ));
+        }
+        Ok(response)
+    };
+
+    let roles = get("")?.text()?;
+    let role = roles
+        .lines()
+        .map(str::trim)
+        .find(|role| !role.is_empty())
+        .ok_or_else(|| anyhow!("No IAM role attached to the instance"))?;
+    Ok(get(role)?.json()?)
+}
+
+/// Path of the shared credentials file, `AWS_SHARED_CREDENTIALS_FILE` if set, so containers
+/// without a home dir can still point at one, `~/.aws/credentials` otherwise.
+fn profile_path() -> Result<PathBuf> {
+    shared_credentials_path(env::var_os("AWS_SHARED_CREDENTIALS_FILE"), dirs::home_dir())
+}
+
+/// Like `profile_path`, with the value of `AWS_SHARED_CREDENTIALS_FILE` and the home dir given.
+pub(crate) fn shared_credentials_path(
+    shared_credentials_file: Option<OsString>,
+    home_dir: Option<PathBuf>,
+) -> Result<PathBuf> {
+    if let Some(path) = shared_credentials_file {
+        if !path.is_empty() {
+            return Ok(PathBuf::from(path));
+        }
+    }
+    let home_dir = home_dir.ok_or_else(|| {
+        anyhow!("Invalid home dir, set AWS_SHARED_CREDENTIALS_FILE to locate the credentials file")
+    })?;
+    Ok(home_dir.join(".aws").join("credentials"))
+}
+
+/// Credentials loaded from an AWS credentials file, reloaded whenever the file changes.
+///
+/// Useful for long running processes where an external tool, like `aws-vault` or an SSO
+/// helper, periodically rotates the keys in the credentials file. rust-s3 buckets check it before
+/// every request with `Bucket::with_profile_watcher`.
+///
+/// # Example
+///
+/// ```no_run
+/// use awscreds::ProfileWatcher;
+///
+/// // Watch the `[default]` profile in `~/.aws/credentials`
+/// let mut watcher = ProfileWatcher::new(None).unwrap();
+/// let credentials = watcher.credentials().clone();
+///
+/// // Later on, pick up rotated keys, if any
+/// if watcher.refresh().unwrap() {
+///     let credentials = watcher.credentials().clone();
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ProfileWatcher {
+    path: PathBuf,
+    section: Option<String>,
+    fingerprint: Option<(SystemTime, u64)>,
+    credentials: Credentials,
+}
+
+impl ProfileWatcher {
+    /// Watch the given profile, defaulting to "default", in `~/.aws/credentials`.
+    pub fn new(section: Option<&str>) -> Result<ProfileWatcher> {
+        ProfileWatcher::with_path(profile_path()?, section)
+    }
+
+    /// Watch the given profile, defaulting to "default", in a credentials file at an explicit
+    /// path.
+    pub fn with_path<P: AsRef<Path>>(path: P, section: Option<&str>) -> Result<ProfileWatcher> {
+        let path = path.as_ref().to_path_buf();
+        let fingerprint = file_fingerprint(&path)?;
+        let credentials = Credentials::from_profile_file(&path, section)?;
+        Ok(ProfileWatcher {
+            path,
+            section: section.map(|s| s.to_string()),
+            fingerprint: Some(fingerprint),
+            credentials,
+        })
+    }
+
+    /// Reload the credentials if the file has been modified since they were last loaded,
+    /// returning whether a reload happened. On error the previously loaded credentials are
+    /// kept, so a file caught mid-write is simply retried on the next call.
+    pub fn refresh(&mut self) -> Result<bool> {
+        let fingerprint = file_fingerprint(&self.path)?;
+        if self.fingerprint == Some(fingerprint) {
+            return Ok(false);
+        }
+        self.credentials = Credentials::from_profile_file(&self.path, self.section.as_deref())?;
+        self.fingerprint = Some(fingerprint);
+        Ok(true)
+    }
+
+    /// The most recently loaded credentials.
+    pub fn credentials(&self) -> &Credentials {
+        &self.credentials
+    }
+
+    /// Path of the watched credentials file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+fn file_fingerprint(path: &Path) -> Result<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path)?;
+    Ok((metadata.modified()?, metadata.len()))
+}
+
 fn from_env_with_default(var: Option<&str>, default: &str) -> Result<String> {
-    let val = var.unwrap_or(default);
-    env::var(val).or_else(|_e| env::var(val)).map_err(|_| {
+    from_lookup_with_default(var, default, |name| env::var(name).ok())
+}
+
+/// Like `from_env_with_default`, looking the variable up with `lookup`.
+fn from_lookup_with_default(
+    var: Option<&str>,
+    default: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String> {
+    lookup(var.unwrap_or(default)).ok_or_else(|| {
         anyhow!(
             "Neither {:?}, nor {} does not exist in the environment",
             var,
@@ -313,3 +738,22 @@ fn from_env_with_default(var: Option<&str>, default: &str) -> Result<String> {
         )
     })
 }
+
+/// Like `from_env_with_default`, falling back to `alias`, a name some older tools use instead of
+/// `default`, unless `var` was given explicitly.
+fn from_env_with_alias(var: Option<&str>, default: &str, alias: &str) -> Result<String> {
+    from_lookup_with_alias(var, default, alias, |name| env::var(name).ok())
+}
+
+/// Like `from_env_with_alias`, looking the variables up with `lookup`.
+pub(crate) fn from_lookup_with_alias(
+    var: Option<&str>,
+    default: &str,
+    alias: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String> {
+    from_lookup_with_default(var, default, &lookup).or_else(|e| match var {
+        None => lookup(alias).ok_or(e),
+        Some(_) => Err(e),
+    })
+}
@@ -3,7 +3,7 @@
 use std::fmt;
 use std::str::{self, FromStr};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 
 /// AWS S3 [region identifier](https://docs.aws.amazon.com/general/latest/gr/rande.html#s3_region),
 /// passing in custom values is also possible, in that case it is up to you to pass a valid endpoint,
@@ -25,8 +25,10 @@ use anyhow::Result;
 /// let endpoint = "https://s3.nl-ams.scw.cloud".to_string();
 /// let region = Region::Custom { region: region_name, endpoint };
 ///
+/// // Or parse a custom endpoint in one go, see `FromStr`
+/// let region: Region = "https://s3.nl-ams.scw.cloud/nl-ams".parse().unwrap();
 /// ```
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub enum Region {
     /// us-east-1
     UsEast1,
@@ -128,11 +130,51 @@ impl fmt::Display for Region {
     }
 }
 
+/// Region name of custom endpoints parsed without one.
+pub const DEFAULT_CUSTOM_REGION: &str = "us-east-1";
+
+/// Parses region names, like `eu-west-1`, as well as custom endpoints given as URLs of the form
+/// `scheme://host[:port][/region]`, so that an endpoint can be configured from a single string:
+///
+/// ```
+/// use awsregion::Region;
+///
+/// let region: Region = "https://minio.example.com/eu-central-1".parse().unwrap();
+/// assert_eq!(
+///     region,
+///     Region::Custom {
+///         region: "eu-central-1".to_string(),
+///         endpoint: "https://minio.example.com".to_string(),
+///     }
+/// );
+///
+/// // The region name defaults to `DEFAULT_CUSTOM_REGION`
+/// let region: Region = "http://localhost:9000".parse().unwrap();
+/// assert_eq!(region.to_string(), "us-east-1");
+/// assert_eq!(region.host(), "localhost:9000");
+/// ```
+///
+/// The URL can also be prefixed with `custom:`, as in `custom:http://localhost:9000`, to make
+/// clear it is not a region name.
+///
+/// Other unknown names parse to a `Region::Custom` with the name as both region and endpoint.
 impl FromStr for Region {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self> {
         use self::Region::*;
+        if let Some(url) = s.strip_prefix("custom:") {
+            return match url.find("://") {
+                Some(pos) => custom_from_url(&url[..pos], &url[pos + 3..]),
+                None => Err(anyhow!(
+                    "Custom endpoint must be of the form custom:scheme://host[:port][/region], got {}",
+                    s
+                )),
+            };
+        }
+        if let Some(pos) = s.find("://") {
+            return custom_from_url(&s[..pos], &s[pos + 3..]);
+        }
         match s {
             "us-east-1" => Ok(UsEast1),
             "us-east-2" => Ok(UsEast2),
@@ -172,7 +214,84 @@ impl FromStr for Region {
     }
 }
 
+fn custom_from_url(scheme: &str, rest: &str) -> Result<Region> {
+    if scheme != "http" && scheme != "https" {
+        return Err(anyhow!(
+            "Custom endpoint scheme must be http or https, got {}",
+            scheme
+        ));
+    }
+    let rest = rest.trim_end_matches('/');
+    let (host, region) = match rest.find('/') {
+        Some(pos) => (&rest[..pos], &rest[pos + 1..]),
+        None => (rest, DEFAULT_CUSTOM_REGION),
+    };
+    if host.is_empty() || region.is_empty() || region.contains('/') {
+        return Err(anyhow!(
+            "Custom endpoint must be of the form scheme://host[:port][/region], got {}://{}",
+            scheme,
+            rest
+        ));
+    }
+    Ok(Region::Custom {
+        region: region.to_string(),
+        endpoint: format!("{}://{}", scheme, host),
+    })
+}
+
+/// Template of AWS S3 endpoints, `{region}` is replaced with the region name and `{suffix}` with
+/// the DNS suffix of its partition, see `aws_dns_suffix`.
+pub const AWS_ENDPOINT_TEMPLATE: &str = "s3.{region}.{suffix}";
+
+/// DNS suffix of the [AWS partition](https://docs.aws.amazon.com/general/latest/gr/aws-arns-and-namespaces.html)
+/// `region` belongs to, GovCloud regions share the one of the commercial partition.
+pub fn aws_dns_suffix(region: &str) -> &'static str {
+    if region.starts_with("cn-") {
+        "amazonaws.com.cn"
+    } else if region.starts_with("us-isob-") {
+        "sc2s.sgov.gov"
+    } else if region.starts_with("us-iso-") {
+        "c2s.ic.gov"
+    } else {
+        "amazonaws.com"
+    }
+}
+
 impl Region {
+    /// AWS region named `region`, its own variant for the regions this crate knows, otherwise,
+    /// like for `us-gov-west-1` or `af-south-1`, a `Custom` one with the endpoint following
+    /// `AWS_ENDPOINT_TEMPLATE`.
+    ///
+    /// ```
+    /// use awsregion::Region;
+    ///
+    /// assert_eq!(Region::aws("eu-central-1"), Region::EuCentral1);
+    ///
+    /// let region = Region::aws("af-south-1");
+    /// assert_eq!(region.endpoint(), "s3.af-south-1.amazonaws.com");
+    /// ```
+    pub fn aws(region: &str) -> Region {
+        match region.parse::<Region>() {
+            Ok(Region::Custom { .. }) | Err(_) => {
+                Region::from_template(region, AWS_ENDPOINT_TEMPLATE)
+            }
+            // Not the DigitalOcean, Yandex or Wasabi regions
+            Ok(known) if known.endpoint().contains(".amazonaws.com") => known,
+            Ok(_) => Region::from_template(region, AWS_ENDPOINT_TEMPLATE),
+        }
+    }
+
+    /// Region with the endpoint built from `template`, see `AWS_ENDPOINT_TEMPLATE` for the
+    /// placeholders, e.g. `s3-fips.{region}.{suffix}` for FIPS endpoints.
+    pub fn from_template(region: &str, template: &str) -> Region {
+        Region::Custom {
+            region: region.to_string(),
+            endpoint: template
+                .replace("{region}", region)
+                .replace("{suffix}", aws_dns_suffix(region)),
+        }
+    }
+
     pub fn endpoint(&self) -> String {
         use self::Region::*;
         match *self {
@@ -245,3 +364,102 @@ fn yandex_object_storage() {
 
     assert_eq!(yandex.to_string(), yandex_region.to_string());
 }
+
+#[test]
+fn region_as_map_key() {
+    use std::collections::HashMap;
+
+    let mut requests = HashMap::new();
+    *requests.entry(Region::EuWest1).or_insert(0) += 1;
+    *requests
+        .entry("eu-west-1".parse::<Region>().unwrap())
+        .or_insert(0) += 1;
+    *requests
+        .entry(Region::Custom {
+            region: "nl-ams".to_string(),
+            endpoint: "https://s3.nl-ams.scw.cloud".to_string(),
+        })
+        .or_insert(0) += 1;
+    *requests
+        .entry(Region::Custom {
+            region: "nl-ams".to_string(),
+            endpoint: "https://other.nl-ams.scw.cloud".to_string(),
+        })
+        .or_insert(0) += 1;
+
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[&Region::EuWest1], 2);
+}
+
+#[test]
+fn endpoint_template() {
+    assert_eq!(
+        Region::aws("af-south-1").host(),
+        "s3.af-south-1.amazonaws.com"
+    );
+    assert_eq!(
+        Region::aws("us-gov-west-1").host(),
+        "s3.us-gov-west-1.amazonaws.com"
+    );
+    assert_eq!(
+        Region::aws("cn-north-1").host(),
+        "s3.cn-north-1.amazonaws.com.cn"
+    );
+    assert_eq!(
+        Region::from_template("us-east-2", "s3-fips.{region}.{suffix}").host(),
+        "s3-fips.us-east-2.amazonaws.com"
+    );
+    assert_eq!(Region::aws("af-south-1").to_string(), "af-south-1");
+    assert_eq!(Region::aws("cn-north-1"), Region::CnNorth1);
+    assert_eq!(Region::aws("us-east-1"), Region::UsEast1);
+    assert_eq!(Region::aws("nyc3").host(), "s3.nyc3.amazonaws.com");
+}
+
+#[test]
+fn parse_custom_url() {
+    assert_eq!(
+        "http://localhost:9000".parse::<Region>().unwrap(),
+        Region::Custom {
+            region: "us-east-1".to_string(),
+            endpoint: "http://localhost:9000".to_string(),
+        }
+    );
+    let region = "https://minio.example.com/eu-central-1/"
+        .parse::<Region>()
+        .unwrap();
+    assert_eq!(region.to_string(), "eu-central-1");
+    assert_eq!(region.scheme(), "https");
+    assert_eq!(region.host(), "minio.example.com");
+
+    assert!("ftp://minio.example.com".parse::<Region>().is_err());
+    assert!("https://".parse::<Region>().is_err());
+    assert!("https://minio.example.com/a/b".parse::<Region>().is_err());
+    assert_eq!("eu-west-1".parse::<Region>().unwrap(), Region::EuWest1);
+}
+
+#[test]
+fn custom_prefix() {
+    let region = "custom:http://localhost:9000".parse::<Region>().unwrap();
+    assert_eq!(
+        region,
+        Region::Custom {
+            region: "us-east-1".to_string(),
+            endpoint: "http://localhost:9000".to_string(),
+        }
+    );
+    assert_eq!(region.scheme(), "http");
+    assert_eq!(region.host(), "localhost:9000");
+    assert_eq!(
+        "custom:https://minio.example.com/eu-central-1"
+            .parse::<Region>()
+            .unwrap()
+            .to_string(),
+        "eu-central-1"
+    );
+    assert!("custom:localhost:9000".parse::<Region>().is_err());
+    assert!("custom:ftp://localhost".parse::<Region>().is_err());
+
+    // Named regions are unaffected
+    assert_eq!("us-east-1".parse::<Region>().unwrap(), Region::UsEast1);
+    assert_eq!("ru-central1".parse::<Region>().unwrap(), Region::Yandex);
+}
@@ -2,10 +2,10 @@ extern crate s3;
 
 use std::str;
 
+use anyhow::Result;
 use s3::bucket::Bucket;
 use s3::creds::Credentials;
 use s3::region::Region;
-use s3::S3Error;
 
 struct Storage {
     name: String,
@@ -17,7 +17,8 @@ struct Storage {
 
 const MESSAGE: &str = "I want to go to S3";
 
-pub fn main() -> Result<(), S3Error> {
+#[tokio::main]
+pub async fn main() -> Result<()> {
     let aws = Storage {
         name: "aws".into(),
         region: "eu-central-1".parse()?,
@@ -65,9 +66,8 @@ pub fn main() -> Result<(), S3Error> {
         let bucket = Bucket::new(&backend.bucket, backend.region, backend.credentials)?;
 
         // List out contents of directory
-        let results = bucket.list_blocking("".to_string(), None)?;
-        for (list, code) in results {
-            assert_eq!(200, code);
+        let results = bucket.list("".to_string(), None).await?;
+        for list in results {
             println!("{:?}", list.contents.len());
         }
 
@@ -75,18 +75,18 @@ pub fn main() -> Result<(), S3Error> {
         // that the s3 library returns the HTTP code even if it indicates a failure
         // (i.e. 404) since we can't predict desired usage. For example, you may
         // expect a 404 to make sure a fi le doesn't exist.
-        //    let (_, code) = bucket.delete("test_file")?;
+        //    let (_, code) = bucket.delete_object("test_file").await?;
         //    assert_eq!(204, code);
 
         // Put a "test_file" with the contents of MESSAGE at the root of the
         // bucket.
-        let (_, code) = bucket.put_object_blocking("test_file", MESSAGE.as_bytes())?;
+        let (_, code) = bucket.put_object("test_file", MESSAGE.as_bytes()).await?;
         // println!("{}", bucket.presign_get("test_file", 604801)?);
         assert_eq!(200, code);
 
         // Get the "test_file" contents and make sure that the returned message
         // matches what we sent.
-        let (data, code) = bucket.get_object_blocking("test_file")?;
+        let (data, code) = bucket.get_object("test_file").await?;
         let string = str::from_utf8(&data)?;
         // println!("{}", string);
         assert_eq!(200, code);
@@ -94,20 +94,24 @@ pub fn main() -> Result<(), S3Error> {
 
         if backend.location_supported {
             // Get bucket location
-            println!("{:?}", bucket.location_blocking()?);
+            println!("{:?}", bucket.location().await?);
         }
 
-        bucket.put_object_tagging_blocking("test_file", &[("test", "tag")])?;
+        bucket
+            .put_object_tagging("test_file", &[("test", "tag")])
+            .await?;
         println!("Tags set");
-        let (tags, _status) = bucket.get_object_tagging_blocking("test_file")?;
+        let (tags, _status) = bucket.get_object_tagging("test_file").await?;
         println!("{:?}", tags);
 
         // Test with random byte array
 
         let random_bytes: Vec<u8> = (0..3072).map(|_| 33).collect();
-        let (_, code) = bucket.put_object_blocking("random.bin", random_bytes.as_slice())?;
+        let (_, code) = bucket
+            .put_object("random.bin", random_bytes.as_slice())
+            .await?;
         assert_eq!(200, code);
-        let (data, code) = bucket.get_object_blocking("random.bin")?;
+        let (data, code) = bucket.get_object("random.bin").await?;
         assert_eq!(code, 200);
         assert_eq!(data.len(), 3072);
         assert_eq!(data, random_bytes);
@@ -1,17 +1,22 @@
 extern crate base64;
 extern crate md5;
 
-use std::io::Write;
+use std::io::{Read, Write};
 
-use attohttpc::header::HeaderName;
+use attohttpc::body::{Body, BodyKind, Bytes};
+use attohttpc::header::{HeaderName, CONTENT_LENGTH, LOCATION};
 
 use super::bucket::Bucket;
 use super::command::Command;
+use crate::creds::Credentials;
+use crate::error::S3Error;
 use chrono::{DateTime, Utc};
 
 use crate::command::HttpMethod;
-use crate::request_trait::Request;
-use anyhow::anyhow;
+use crate::request_trait::{
+    is_not_found, is_region_redirect, is_server_error, region_redirect, temporary_redirect,
+    BodyStream, Request, Retries, MAX_TEMPORARY_REDIRECTS,
+};
 use anyhow::Result;
 // static CLIENT: Lazy<Client> = Lazy::new(|| {
 //     if cfg!(feature = "no-verify-ssl") {
@@ -32,6 +37,7 @@ pub struct AttoRequest<'a> {
     pub command: Command<'a>,
     pub datetime: DateTime<Utc>,
     pub sync: bool,
+    pub credentials: Credentials,
 }
 
 impl<'a> Request for AttoRequest<'a> {
@@ -46,6 +52,10 @@ impl<'a> Request for AttoRequest<'a> {
         self.bucket.clone()
     }
 
+    fn credentials(&self) -> Credentials {
+        self.credentials.clone()
+    }
+
     fn command(&self) -> Command {
         self.command.clone()
     }
@@ -55,37 +65,75 @@ impl<'a> Request for AttoRequest<'a> {
     }
 
     fn response(&self) -> Result<Self::Response> {
-        // Build headers
-        let headers = match self.headers() {
-            Ok(headers) => headers,
-            Err(e) => return Err(e),
-        };
-
-        let mut session = attohttpc::Session::new();
-
-        for (name, value) in headers.iter() {
-            session.header(HeaderName::from_bytes(name.as_ref()).unwrap(), value);
-        }
-
-        let request = match self.command.http_verb() {
-            HttpMethod::Get => session.get(self.url()),
-            HttpMethod::Delete => session.delete(self.url()),
-            HttpMethod::Put => session.put(self.url()),
-            HttpMethod::Post => session.post(self.url()),
-            HttpMethod::Head => session.head(self.url()),
-        };
-
-        let response = request.bytes(&self.request_body()).send()?;
-
-        if cfg!(feature = "fail-on-err") && response.status().as_u16() >= 400 {
-            return Err(anyhow!(
-                "Request failed with code {}\n{}",
-                response.status().as_u16(),
-                response.text()?
-            ));
+        self.bucket.refresh_expired_credentials()?;
+        let mut bucket = self.bucket.clone();
+        let mut redirects = 0;
+        let mut region_redirected = false;
+        let mut retries = Retries::new(self.bucket, &self.command);
+        loop {
+            let result = AttoRequest::new(&bucket, self.path, self.command.clone()).send();
+            self.bucket
+                .invalidate_head_cache_for(self.path, &self.command);
+            let connection_failed = result.as_ref().err().is_some_and(|e| {
+                e.chain().any(|cause| {
+                    matches!(
+                        cause.downcast_ref::<attohttpc::Error>().map(|e| e.kind()),
+                        Some(attohttpc::ErrorKind::Io(_))
+                    )
+                })
+            });
+            let transient = match &result {
+                Ok(response) => response.status().is_server_error(),
+                Err(e) => is_server_error(e) || connection_failed,
+            };
+            if transient {
+                if let Some(delay) = retries.next_delay() {
+                    std::thread::sleep(delay);
+                    continue;
+                }
+            }
+            let not_yet_created = match &result {
+                Ok(response) => response.status().as_u16() == 404,
+                Err(e) => is_not_found(e) || connection_failed,
+            };
+            if not_yet_created {
+                if let Some(delay) = retries.next_post_create_delay() {
+                    std::thread::sleep(delay);
+                    continue;
+                }
+            }
+            let response = result?;
+            if !region_redirected {
+                let bucket_region = response
+                    .headers()
+                    .get("x-amz-bucket-region")
+                    .and_then(|region| region.to_str().ok());
+                if let Some(redirected) =
+                    region_redirect(&bucket, response.status().as_u16(), bucket_region)?
+                {
+                    bucket = redirected;
+                    region_redirected = true;
+                    continue;
+                }
+            }
+            if redirects < MAX_TEMPORARY_REDIRECTS {
+                let location = response
+                    .headers()
+                    .get(LOCATION)
+                    .and_then(|location| location.to_str().ok());
+                if let Some(redirected) = temporary_redirect(
+                    &bucket,
+                    &self.command,
+                    response.status().as_u16(),
+                    location,
+                )? {
+                    bucket = redirected;
+                    redirects += 1;
+                    continue;
+                }
+            }
+            return Ok(response);
         }
-
-        Ok(response)
     }
 
     fn response_data(&self, etag: bool) -> Result<(Vec<u8>, u16)> {
@@ -94,6 +142,7 @@ impl<'a> Request for AttoRequest<'a> {
         let headers = response.headers().clone();
         let etag_header = headers.get("ETag");
         let body = response.bytes()?;
+        self.log_signature_mismatch(status_code, &body);
         let mut body_vec = Vec::new();
         body_vec.extend_from_slice(&body[..]);
         if etag {
@@ -108,22 +157,171 @@ impl<'a> Request for AttoRequest<'a> {
         let response = self.response()?;
 
         let status_code = response.status();
-        let stream = response.bytes()?;
-
-        writer.write_all(&stream)?;
+        response.write_to(writer)?;
 
         Ok(status_code.as_u16())
     }
 
+    fn response_data_to_writer_if_status<T: Write>(
+        &self,
+        writer: &mut T,
+        expected: u16,
+    ) -> Result<(u16, u64)> {
+        let response = self.response()?;
+        let status_code = response.status().as_u16();
+        if status_code != expected {
+            let body = response.bytes()?;
+            self.log_signature_mismatch(status_code, &body);
+            return Ok((status_code, 0));
+        }
+
+        let written = response.write_to(writer)?;
+
+        Ok((status_code, written))
+    }
+
+    fn response_data_streamed(&self, body: BodyStream<'_>) -> Result<(Vec<u8>, u16)> {
+        self.bucket.refresh_expired_credentials()?;
+        let body = StreamedBody {
+            pieces: Some(body),
+            length: self.command.content_length() as u64,
+        };
+        let result = self.send_body(body);
+        self.bucket
+            .invalidate_head_cache_for(self.path, &self.command);
+        let response = result?;
+        let status_code = response.status().as_u16();
+        let etag = match response.headers().get("ETag") {
+            Some(etag) => Some(etag.to_str()?.as_bytes().to_vec()),
+            None => None,
+        };
+        let body = response.bytes()?;
+        self.log_signature_mismatch(status_code, &body);
+        Ok((etag.unwrap_or(body), status_code))
+    }
+
     fn response_header(&self) -> Result<(Self::HeaderMap, u16)> {
         let response = self.response()?;
         let status_code = response.status().as_u16();
         let headers = response.headers().clone();
         Ok((headers, status_code))
     }
+
+    fn response_data_with_headers(&self) -> Result<(Vec<u8>, Self::HeaderMap, u16)> {
+        let response = self.response()?;
+        let status_code = response.status().as_u16();
+        let headers = response.headers().clone();
+        let body = response.bytes()?;
+        self.log_signature_mismatch(status_code, &body);
+        Ok((body, headers, status_code))
+    }
+
+    fn response_data_with_max_size(&self, max_body_size: u64) -> Result<(Vec<u8>, u16)> {
+        let response = self.response()?;
+        let status_code = response.status().as_u16();
+        let content_length = response
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok());
+        if let Some(content_length) = content_length {
+            if content_length > max_body_size {
+                return Err(S3Error::BodyTooLarge {
+                    limit: max_body_size,
+                    content_length: Some(content_length),
+                }
+                .into());
+            }
+        }
+
+        // One byte past the limit is enough to tell that the body is too large
+        let (_, _, reader) = response.split();
+        let mut body = Vec::new();
+        reader.take(max_body_size + 1).read_to_end(&mut body)?;
+        if body.len() as u64 > max_body_size {
+            return Err(S3Error::BodyTooLarge {
+                limit: max_body_size,
+                content_length: None,
+            }
+            .into());
+        }
+        Ok((body, status_code))
+    }
+}
+
+/// Body written as its pieces are produced, see `Request::response_data_streamed`.
+struct StreamedBody<'a> {
+    pieces: Option<BodyStream<'a>>,
+    length: u64,
+}
+
+impl Body for StreamedBody<'_> {
+    fn kind(&mut self) -> std::io::Result<BodyKind> {
+        Ok(BodyKind::KnownLength(self.length))
+    }
+
+    fn write<W: Write>(&mut self, mut writer: W) -> std::io::Result<()> {
+        let pieces = self.pieces.take().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::Other, "A streamed body can't be resent")
+        })?;
+        for piece in pieces {
+            writer.write_all(&piece?)?;
+        }
+        Ok(())
+    }
 }
 
 impl<'a> AttoRequest<'a> {
+    fn send(&self) -> Result<attohttpc::Response> {
+        self.send_body(Bytes(self.request_body()))
+    }
+
+    fn send_body<B: Body>(&self, body: B) -> Result<attohttpc::Response> {
+        // Build headers
+        let (headers, signed) = self.signed_headers()?;
+
+        let mut session = attohttpc::Session::new();
+        // Redirects are handled in `response`, as the request has to be signed for the new host
+        session.follow_redirects(false);
+        if let Some(timeout) = self.bucket.request_timeout() {
+            session.timeout(timeout);
+        }
+        if let Some(timeout) = self.bucket.connect_timeout() {
+            session.connect_timeout(timeout);
+        }
+
+        for (name, value) in headers.iter() {
+            session.header(HeaderName::from_bytes(name.as_ref()).unwrap(), value);
+        }
+
+        let request = match self.command.http_verb() {
+            HttpMethod::Get => session.get(self.url()),
+            HttpMethod::Delete => session.delete(self.url()),
+            HttpMethod::Put => session.put(self.url()),
+            HttpMethod::Post => session.post(self.url()),
+            HttpMethod::Head => session.head(self.url()),
+        };
+
+        let response = request.body(body).send();
+        self.log_attempt(signed, response.as_ref().ok().map(|r| r.status().as_u16()));
+        let response = response?;
+
+        let status = response.status().as_u16();
+        let bucket_region = response
+            .headers()
+            .get("x-amz-bucket-region")
+            .and_then(|region| region.to_str().ok());
+        if cfg!(feature = "fail-on-err")
+            && status >= 400
+            && !is_region_redirect(self.bucket, status, bucket_region)
+        {
+            let body = response.bytes()?;
+            return Err(self.error_response(status, &body).into());
+        }
+
+        Ok(response)
+    }
+
     pub fn new<'b>(bucket: &'b Bucket, path: &'b str, command: Command<'b>) -> AttoRequest<'b> {
         AttoRequest {
             bucket,
@@ -131,6 +329,7 @@ impl<'a> AttoRequest<'a> {
             command,
             datetime: Utc::now(),
             sync: false,
+            credentials: bucket.credentials(),
         }
     }
 }
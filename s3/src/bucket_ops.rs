@@ -1,3 +1,4 @@
+use crate::serde_types::AwsError;
 use crate::{Bucket, Region};
 use anyhow::Result;
 
@@ -105,9 +106,16 @@ impl BucketConfiguration {
         self.location_constraint = Some(region)
     }
 
+    /// Body of the create request, S3 rejects a `LocationConstraint` of us-east-1, including
+    /// one of a custom region named us-east-1, so it is left out for those.
     pub fn location_constraint_payload(&self) -> Option<String> {
         if let Some(ref location_constraint) = self.location_constraint {
-            if location_constraint == &Region::UsEast1 {
+            let us_east_1 = match location_constraint {
+                Region::UsEast1 => true,
+                Region::Custom { region, .. } => region == "us-east-1",
+                _ => false,
+            };
+            if us_east_1 {
                 return None;
             }
             Some(format!(
@@ -175,4 +183,12 @@ impl CreateBucketResponse {
     pub fn success(&self) -> bool {
         self.response_code == 200
     }
+
+    /// The bucket existed already and belongs to the caller, a `409 BucketAlreadyOwnedByYou`,
+    /// which setup code creating buckets idempotently can treat like a success.
+    pub fn already_owned(&self) -> bool {
+        self.response_code == 409
+            && serde_xml_rs::from_str::<AwsError>(&self.response_text)
+                .is_ok_and(|error| error.code() == "BucketAlreadyOwnedByYou")
+    }
 }
@@ -1,4 +1,6 @@
-use crate::serde_types::CompleteMultipartUploadData;
+use crate::serde_types::{
+    CompleteMultipartUploadData, DeleteObjectsData, InventoryConfiguration, ObjectLockConfiguration,
+};
 
 use crate::EMPTY_PAYLOAD_SHA;
 use sha2::{Digest, Sha256};
@@ -25,6 +27,10 @@ impl fmt::Display for HttpMethod {
     }
 }
 use crate::bucket_ops::BucketConfiguration;
+use crate::object_ops::{
+    AwsChunkedEncoder, ChecksumAlgorithm, CopyObjectOptions, PutObjectOptions, ResponseOverrides,
+};
+use crate::select::SelectObjectContentData;
 use http::HeaderMap;
 
 #[derive(Clone, Debug)]
@@ -53,6 +59,9 @@ impl<'a> Multipart<'a> {
 pub enum Command<'a> {
     HeadObject,
     DeleteObject,
+    DeleteObjects {
+        data: DeleteObjectsData,
+    },
     DeleteObjectTagging,
     GetObject,
     GetObjectTorrent,
@@ -60,18 +69,41 @@ pub enum Command<'a> {
         start: u64,
         end: Option<u64>,
     },
+    /// Range of an object, only if its entity tag is still `etag`
+    GetObjectRangeIfMatch {
+        start: u64,
+        end: u64,
+        etag: &'a str,
+    },
+    GetObjectPart {
+        part_number: u32,
+    },
     GetObjectTagging,
     PutObject {
         content: &'a [u8],
         content_type: &'a str,
         multipart: Option<Multipart<'a>>,
+        sha256: Option<&'a str>,
+        options: Option<&'a PutObjectOptions>,
+    },
+    /// `aws-chunked` encoded put, with the checksum of the content as trailer. The body is
+    /// streamed, see `Request::response_data_streamed`.
+    PutObjectWithTrailingChecksum {
+        decoded_length: u64,
+        algorithm: ChecksumAlgorithm,
+        content_type: &'a str,
     },
     PutObjectTagging {
         tags: &'a str,
     },
+    CopyObject {
+        source_bucket: &'a str,
+        from: &'a str,
+        options: Option<&'a CopyObjectOptions>,
+    },
     ListMultipartUploads {
-        prefix: Option<&'a str>,
-        delimiter: Option<&'a str>,
+        prefix: Option<String>,
+        delimiter: Option<String>,
         key_marker: Option<String>,
         max_uploads: Option<usize>,
     },
@@ -82,15 +114,25 @@ pub enum Command<'a> {
         start_after: Option<String>,
         max_keys: Option<usize>,
     },
+    ListV1 {
+        prefix: String,
+        delimiter: Option<String>,
+        marker: Option<String>,
+        max_keys: Option<usize>,
+    },
     GetBucketLocation,
     PresignGet {
         expiry_secs: u32,
+        response_overrides: Option<ResponseOverrides>,
     },
     PresignPut {
         expiry_secs: u32,
         custom_headers: Option<HeaderMap>,
     },
-    InitiateMultipartUpload,
+    InitiateMultipartUpload {
+        content_type: &'a str,
+        checksum_algorithm: Option<ChecksumAlgorithm>,
+    },
     UploadPart {
         part_number: u32,
         content: &'a [u8],
@@ -107,6 +149,25 @@ pub enum Command<'a> {
         config: BucketConfiguration,
     },
     DeleteBucket,
+    GetBucketInventoryConfiguration {
+        id: &'a str,
+    },
+    PutBucketInventoryConfiguration {
+        configuration: &'a InventoryConfiguration,
+    },
+    DeleteBucketInventoryConfiguration {
+        id: &'a str,
+    },
+    ListBucketInventoryConfigurations {
+        continuation_token: Option<String>,
+    },
+    SelectObjectContent {
+        data: SelectObjectContentData,
+    },
+    GetObjectLockConfiguration,
+    PutObjectLockConfiguration {
+        configuration: &'a ObjectLockConfiguration,
+    },
 }
 
 impl<'a> Command<'a> {
@@ -115,23 +176,35 @@ impl<'a> Command<'a> {
             Command::GetObject
             | Command::GetObjectTorrent
             | Command::GetObjectRange { .. }
+            | Command::GetObjectRangeIfMatch { .. }
+            | Command::GetObjectPart { .. }
             | Command::ListBucket { .. }
+            | Command::ListV1 { .. }
             | Command::GetBucketLocation
             | Command::GetObjectTagging
             | Command::ListMultipartUploads { .. }
+            | Command::GetBucketInventoryConfiguration { .. }
+            | Command::ListBucketInventoryConfigurations { .. }
+            | Command::GetObjectLockConfiguration
             | Command::PresignGet { .. } => HttpMethod::Get,
             Command::PutObject { .. }
+            | Command::PutObjectWithTrailingChecksum { .. }
             | Command::PutObjectTagging { .. }
+            | Command::CopyObject { .. }
             | Command::PresignPut { .. }
             | Command::UploadPart { .. }
-            | Command::CreateBucket { .. } => HttpMethod::Put,
+            | Command::CreateBucket { .. }
+            | Command::PutBucketInventoryConfiguration { .. }
+            | Command::PutObjectLockConfiguration { .. } => HttpMethod::Put,
             Command::DeleteObject
             | Command::DeleteObjectTagging
             | Command::AbortMultipartUpload { .. }
+            | Command::DeleteBucketInventoryConfiguration { .. }
             | Command::DeleteBucket => HttpMethod::Delete,
-            Command::InitiateMultipartUpload | Command::CompleteMultipartUpload { .. } => {
-                HttpMethod::Post
-            }
+            Command::InitiateMultipartUpload { .. }
+            | Command::CompleteMultipartUpload { .. }
+            | Command::DeleteObjects { .. }
+            | Command::SelectObjectContent { .. } => HttpMethod::Post,
             Command::HeadObject => HttpMethod::Head,
         }
     }
@@ -139,9 +212,22 @@ impl<'a> Command<'a> {
     pub fn content_length(&self) -> usize {
         match &self {
             Command::PutObject { content, .. } => content.len(),
+            Command::PutObjectWithTrailingChecksum {
+                decoded_length,
+                algorithm,
+                ..
+            } => AwsChunkedEncoder::encoded_length(*decoded_length, *algorithm) as usize,
             Command::PutObjectTagging { tags } => tags.len(),
             Command::UploadPart { content, .. } => content.len(),
             Command::CompleteMultipartUpload { data, .. } => data.len(),
+            Command::DeleteObjects { data } => data.len(),
+            Command::SelectObjectContent { data } => data.len(),
+            Command::PutBucketInventoryConfiguration { configuration } => {
+                configuration.to_string().len()
+            }
+            Command::PutObjectLockConfiguration { configuration } => {
+                configuration.to_string().len()
+            }
             Command::CreateBucket { config } => {
                 if let Some(payload) = config.location_constraint_payload() {
                     Vec::from(payload).len()
@@ -155,19 +241,33 @@ impl<'a> Command<'a> {
 
     pub fn content_type(&self) -> String {
         match self {
-            Command::PutObject { content_type, .. } => content_type.to_string(),
-            Command::CompleteMultipartUpload { .. } => "application/xml".into(),
+            Command::PutObject { content_type, .. }
+            | Command::PutObjectWithTrailingChecksum { content_type, .. }
+            | Command::InitiateMultipartUpload { content_type, .. } => content_type.to_string(),
+            Command::CompleteMultipartUpload { .. }
+            | Command::DeleteObjects { .. }
+            | Command::PutBucketInventoryConfiguration { .. }
+            | Command::SelectObjectContent { .. }
+            | Command::PutObjectLockConfiguration { .. } => "application/xml".into(),
             _ => "text/plain".into(),
         }
     }
 
     pub fn sha256(&self) -> String {
         match &self {
+            Command::PutObject {
+                sha256: Some(sha256),
+                ..
+            } => sha256.to_string(),
             Command::PutObject { content, .. } => {
                 let mut sha = Sha256::default();
                 sha.update(content);
                 hex::encode(sha.finalize().as_slice())
             }
+            // The trailer can't be covered by the signature of the request
+            Command::PutObjectWithTrailingChecksum { .. } => {
+                "STREAMING-UNSIGNED-PAYLOAD-TRAILER".into()
+            }
             Command::PutObjectTagging { tags } => {
                 let mut sha = Sha256::default();
                 sha.update(tags.as_bytes());
@@ -178,6 +278,26 @@ impl<'a> Command<'a> {
                 sha.update(data.to_string().as_bytes());
                 hex::encode(sha.finalize().as_slice())
             }
+            Command::DeleteObjects { data } => {
+                let mut sha = Sha256::default();
+                sha.update(data.to_string().as_bytes());
+                hex::encode(sha.finalize().as_slice())
+            }
+            Command::SelectObjectContent { data } => {
+                let mut sha = Sha256::default();
+                sha.update(data.to_string().as_bytes());
+                hex::encode(sha.finalize().as_slice())
+            }
+            Command::PutBucketInventoryConfiguration { configuration } => {
+                let mut sha = Sha256::default();
+                sha.update(configuration.to_string().as_bytes());
+                hex::encode(sha.finalize().as_slice())
+            }
+            Command::PutObjectLockConfiguration { configuration } => {
+                let mut sha = Sha256::default();
+                sha.update(configuration.to_string().as_bytes());
+                hex::encode(sha.finalize().as_slice())
+            }
             Command::CreateBucket { config } => {
                 if let Some(payload) = config.location_constraint_payload() {
                     let mut sha = Sha256::default();
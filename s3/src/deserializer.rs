@@ -1,3 +1,4 @@
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
 use serde::de::*;
 
 pub fn bool_deserializer<'de, D>(d: D) -> Result<bool, D::Error>
@@ -14,3 +15,117 @@ where
         ))),
     }
 }
+
+/// Parses timestamps as returned by S3 and S3 compatible APIs, with or without fractional
+/// seconds, and with either a `Z` or a numeric offset. Timestamps without any offset are
+/// assumed to be UTC.
+pub fn parse_datetime(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(s) {
+        return Some(datetime.with_timezone(&Utc));
+    }
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f")
+        .ok()
+        .map(|naive| Utc.from_utc_datetime(&naive))
+}
+
+/// Parses an HTTP date such as `Wed, 21 Oct 2015 07:28:00 GMT`, falling back to ISO 8601.
+pub fn parse_http_date(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(s.trim())
+        .ok()
+        .map(|datetime| datetime.with_timezone(&Utc))
+        .or_else(|| parse_datetime(s))
+}
+
+pub fn optional_http_date_deserializer<'de, D>(d: D) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = Option::<String>::deserialize(d)?;
+    Ok(s.as_deref().and_then(parse_http_date))
+}
+
+pub fn datetime_deserializer<'de, D>(d: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(d)?;
+    parse_datetime(&s)
+        .ok_or_else(|| D::Error::custom(format!("got {}, but expected an ISO 8601 date", s)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_xml_rs as serde_xml;
+
+    #[derive(Deserialize, Debug)]
+    struct Timestamp {
+        #[serde(rename = "LastModified", deserialize_with = "datetime_deserializer")]
+        last_modified: DateTime<Utc>,
+    }
+
+    fn last_modified(value: &str) -> DateTime<Utc> {
+        let xml = format!(
+            "<Contents><LastModified>{}</LastModified></Contents>",
+            value
+        );
+        let timestamp: Timestamp = serde_xml::from_str(&xml).unwrap();
+        timestamp.last_modified
+    }
+
+    #[test]
+    fn test_datetime_with_millis() {
+        assert_eq!(
+            last_modified("2020-01-02T03:04:05.000Z"),
+            Utc.ymd(2020, 1, 2).and_hms(3, 4, 5)
+        );
+    }
+
+    #[test]
+    fn test_datetime_without_fraction() {
+        assert_eq!(
+            last_modified("2020-01-02T03:04:05Z"),
+            Utc.ymd(2020, 1, 2).and_hms(3, 4, 5)
+        );
+    }
+
+    #[test]
+    fn test_datetime_with_nanos() {
+        assert_eq!(
+            last_modified("2020-01-02T03:04:05.123456789Z"),
+            Utc.ymd(2020, 1, 2).and_hms_nano(3, 4, 5, 123_456_789)
+        );
+    }
+
+    #[test]
+    fn test_datetime_with_offset() {
+        assert_eq!(
+            last_modified("2020-01-02T03:04:05+00:00"),
+            Utc.ymd(2020, 1, 2).and_hms(3, 4, 5)
+        );
+        assert_eq!(
+            last_modified("2020-01-02T03:04:05.000+00:00"),
+            Utc.ymd(2020, 1, 2).and_hms(3, 4, 5)
+        );
+        assert_eq!(
+            last_modified("2020-01-02T05:04:05+02:00"),
+            Utc.ymd(2020, 1, 2).and_hms(3, 4, 5)
+        );
+    }
+
+    #[test]
+    fn test_datetime_without_offset() {
+        assert_eq!(
+            last_modified("2020-01-02T03:04:05.000"),
+            Utc.ymd(2020, 1, 2).and_hms(3, 4, 5)
+        );
+    }
+
+    #[test]
+    fn test_datetime_invalid() {
+        let xml = "<Contents><LastModified>yesterday</LastModified></Contents>";
+        assert!(serde_xml::from_str::<Timestamp>(xml).is_err());
+    }
+}
@@ -0,0 +1,342 @@
+use std::fmt;
+
+use crate::serde_types::AwsError;
+
+/// Error for requests that S3 did not answer successfully.
+///
+/// Methods return `anyhow::Result`, downcast the error to get to the details:
+///
+/// ```
+/// use s3::S3Error;
+///
+/// # let error: anyhow::Error = S3Error::Http { status: 403, body: "<Error/>".to_string() }.into();
+/// if let Some(s3_error) = error.downcast_ref::<S3Error>() {
+///     println!("{:?}", s3_error.response_body());
+/// }
+/// ```
+///
+/// Requests that fail before S3 answers, like timeouts or refused connections, carry the error
+/// of the HTTP client instead, see `transport_error`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum S3Error {
+    /// Unsuccessful HTTP response, with the raw response body, usually an `<Error>` XML document.
+    Http { status: u16, body: String },
+    /// S3 computed a different signature, `canonical_request` and `string_to_sign` are the ones
+    /// S3 computed, `local_canonical_request` the one the request was signed with, when known.
+    SignatureDoesNotMatch {
+        status: u16,
+        body: String,
+        canonical_request: Option<String>,
+        string_to_sign: Option<String>,
+        local_canonical_request: Option<String>,
+    },
+    /// The body S3 received does not match the `Content-MD5` it was sent with, it was corrupted
+    /// on the way, see `Bucket::put_verified`.
+    BadDigest { status: u16, body: String },
+    /// The response did not have the `Content-Type` the caller expected, see
+    /// `Bucket::get_object_expecting_content_type`.
+    UnexpectedContentType {
+        expected: String,
+        actual: Option<String>,
+    },
+    /// Multipart upload part numbers run from 1 to 10000, caught before sending the part, see
+    /// `object_ops::validate_part`.
+    InvalidPartNumber { part_number: u32 },
+    /// An inclusive byte range ending before it starts, caught before sending the request, see
+    /// `Bucket::get_object_range`.
+    InvalidRange { start: u64, end: u64 },
+    /// The response body is larger than the caller allowed, `content_length` is set when the
+    /// `Content-Length` header gave it away before reading, see `Bucket::get_object_with_max_size`.
+    BodyTooLarge {
+        limit: u64,
+        content_length: Option<u64>,
+    },
+}
+
+#[derive(Deserialize, Debug)]
+struct ErrorBody {
+    #[serde(rename = "Code")]
+    code: String,
+    #[serde(rename = "CanonicalRequest")]
+    canonical_request: Option<String>,
+    #[serde(rename = "StringToSign")]
+    string_to_sign: Option<String>,
+}
+
+/// Line by line comparison of two multi-line strings, listing only the lines that differ.
+fn line_diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let mut diff = String::new();
+    for i in 0..expected.len().max(actual.len()) {
+        let (e, a) = (expected.get(i), actual.get(i));
+        if e != a {
+            if let Some(e) = e {
+                diff.push_str(&format!("-{}\n", e));
+            }
+            if let Some(a) = a {
+                diff.push_str(&format!("+{}\n", a));
+            }
+        }
+    }
+    diff
+}
+
+impl S3Error {
+    pub fn http(status: u16, body: &[u8]) -> Self {
+        let body = String::from_utf8_lossy(body).to_string();
+        match serde_xml_rs::from_str::<ErrorBody>(&body) {
+            Ok(error) if error.code == "SignatureDoesNotMatch" => S3Error::SignatureDoesNotMatch {
+                status,
+                canonical_request: error.canonical_request,
+                string_to_sign: error.string_to_sign,
+                local_canonical_request: None,
+                body,
+            },
+            Ok(error) if error.code == "BadDigest" => S3Error::BadDigest { status, body },
+            _ => S3Error::Http { status, body },
+        }
+    }
+
+    /// Attach the canonical request the request was signed with, to compare with the one S3
+    /// computed, has no effect on errors other than `SignatureDoesNotMatch`.
+    pub fn with_local_canonical_request(self, local: String) -> Self {
+        match self {
+            S3Error::SignatureDoesNotMatch {
+                status,
+                body,
+                canonical_request,
+                string_to_sign,
+                ..
+            } => S3Error::SignatureDoesNotMatch {
+                status,
+                body,
+                canonical_request,
+                string_to_sign,
+                local_canonical_request: Some(local),
+            },
+            error => error,
+        }
+    }
+
+    /// HTTP status code of the response.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            S3Error::Http { status, .. }
+            | S3Error::SignatureDoesNotMatch { status, .. }
+            | S3Error::BadDigest { status, .. } => Some(*status),
+            S3Error::UnexpectedContentType { .. }
+            | S3Error::InvalidPartNumber { .. }
+            | S3Error::InvalidRange { .. }
+            | S3Error::BodyTooLarge { .. } => None,
+        }
+    }
+
+    /// Raw body of the response, useful to inspect the full `<Error>` XML, including any
+    /// nonstandard diagnostic fields some gateways add.
+    pub fn response_body(&self) -> Option<&str> {
+        match self {
+            S3Error::Http { body, .. }
+            | S3Error::SignatureDoesNotMatch { body, .. }
+            | S3Error::BadDigest { body, .. } => Some(body),
+            S3Error::UnexpectedContentType { .. }
+            | S3Error::InvalidPartNumber { .. }
+            | S3Error::InvalidRange { .. }
+            | S3Error::BodyTooLarge { .. } => None,
+        }
+    }
+
+    /// The parsed `<Error>` document of the response, to match on its `code` rather than on the
+    /// raw body, `None` when there is no response or its body is not an S3 error.
+    ///
+    /// ```
+    /// use s3::S3Error;
+    ///
+    /// # let error: anyhow::Error = S3Error::http(404, b"<Error><Code>NoSuchKey</Code></Error>").into();
+    /// match error.downcast_ref::<S3Error>().and_then(S3Error::aws_error) {
+    ///     Some(aws_error) if aws_error.code() == "NoSuchKey" => println!("Not found"),
+    ///     _ => println!("{}", error),
+    /// }
+    /// ```
+    pub fn aws_error(&self) -> Option<AwsError> {
+        self.response_body()
+            .and_then(|body| serde_xml_rs::from_str(body).ok())
+    }
+
+    /// For signature mismatches, the lines of the canonical request S3 computed (`-`) that differ
+    /// from the local one (`+`), usually pointing straight at the offending header or encoding.
+    pub fn canonical_request_diff(&self) -> Option<String> {
+        match self {
+            S3Error::SignatureDoesNotMatch {
+                canonical_request: Some(remote),
+                local_canonical_request: Some(local),
+                ..
+            } => Some(line_diff(remote, local)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for S3Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            S3Error::Http { status, body } => {
+                write!(f, "Request failed with code {}\n{}", status, body)
+            }
+            S3Error::SignatureDoesNotMatch {
+                status,
+                canonical_request,
+                string_to_sign,
+                ..
+            } => {
+                write!(
+                    f,
+                    "Signature does not match, request failed with code {}",
+                    status
+                )?;
+                if let Some(canonical_request) = canonical_request {
+                    write!(f, "\nCanonical request:\n{}", canonical_request)?;
+                }
+                if let Some(string_to_sign) = string_to_sign {
+                    write!(f, "\nString to sign:\n{}", string_to_sign)?;
+                }
+                if let Some(diff) = self.canonical_request_diff() {
+                    write!(f, "\nCanonical request diff (-S3, +local):\n{}", diff)?;
+                }
+                Ok(())
+            }
+            S3Error::BadDigest { status, .. } => write!(
+                f,
+                "Content-MD5 does not match the body S3 received, request failed with code {}",
+                status
+            ),
+            S3Error::UnexpectedContentType { expected, actual } => match actual {
+                Some(actual) => write!(f, "Expected Content-Type {}, got {}", expected, actual),
+                None => write!(f, "Expected Content-Type {}, got none", expected),
+            },
+            S3Error::InvalidPartNumber { part_number } => write!(
+                f,
+                "Part numbers must be between 1 and 10000, got {}",
+                part_number
+            ),
+            S3Error::InvalidRange { start, end } => {
+                write!(f, "Byte range {}-{} ends before it starts", start, end)
+            }
+            S3Error::BodyTooLarge {
+                limit,
+                content_length,
+            } => match content_length {
+                Some(content_length) => write!(
+                    f,
+                    "Response body of {} bytes exceeds the limit of {} bytes",
+                    content_length, limit
+                ),
+                None => write!(f, "Response body exceeds the limit of {} bytes", limit),
+            },
+        }
+    }
+}
+
+impl std::error::Error for S3Error {}
+
+/// The `reqwest::Error` a request failed with, if it failed at the transport level, for example
+/// to only retry on `is_timeout()` or `is_connect()`.
+///
+/// ```
+/// # let error = anyhow::anyhow!("Not a transport error");
+/// if let Some(reqwest_error) = s3::error::transport_error(&error) {
+///     if reqwest_error.is_timeout() {
+///         // Retry
+///     }
+/// }
+/// ```
+#[cfg(feature = "with-tokio")]
+pub fn transport_error(error: &anyhow::Error) -> Option<&reqwest::Error> {
+    error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<reqwest::Error>())
+}
+
+#[cfg(test)]
+mod test {
+    use super::S3Error;
+
+    #[test]
+    fn test_response_body_survives_anyhow() {
+        let body = "<Error><Code>AccessDenied</Code><GatewayId>42</GatewayId></Error>";
+        let error: anyhow::Error = S3Error::http(403, body.as_bytes()).into();
+        let s3_error = error.downcast_ref::<S3Error>().unwrap();
+        assert_eq!(s3_error.status(), Some(403));
+        assert_eq!(s3_error.response_body(), Some(body));
+        assert_eq!(
+            error.to_string(),
+            format!("Request failed with code 403\n{}", body)
+        );
+        assert_eq!(s3_error.aws_error().unwrap().code(), "AccessDenied");
+        assert_eq!(S3Error::http(502, b"Bad Gateway").aws_error(), None);
+    }
+
+    #[test]
+    fn test_signature_does_not_match() {
+        let body = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+            <Error><Code>SignatureDoesNotMatch</Code>\
+            <Message>The request signature we calculated does not match the signature you provided.</Message>\
+            <StringToSign>AWS4-HMAC-SHA256</StringToSign>\
+            <CanonicalRequest>GET\n/test.file\n\nhost:rust-s3.s3.amazonaws.com</CanonicalRequest>\
+            </Error>";
+        let error = S3Error::http(403, body.as_bytes());
+        match &error {
+            S3Error::SignatureDoesNotMatch {
+                canonical_request,
+                string_to_sign,
+                ..
+            } => {
+                assert_eq!(
+                    canonical_request.as_deref(),
+                    Some("GET\n/test.file\n\nhost:rust-s3.s3.amazonaws.com")
+                );
+                assert_eq!(string_to_sign.as_deref(), Some("AWS4-HMAC-SHA256"));
+            }
+            _ => panic!("Expected SignatureDoesNotMatch, got {:?}", error),
+        }
+        assert_eq!(error.response_body(), Some(body));
+        assert!(error.canonical_request_diff().is_none());
+
+        let error = error.with_local_canonical_request(
+            "GET\n/test%20file\n\nhost:rust-s3.s3.amazonaws.com".to_string(),
+        );
+        assert_eq!(
+            error.canonical_request_diff().as_deref(),
+            Some("-/test.file\n+/test%20file\n")
+        );
+    }
+
+    #[test]
+    fn test_bad_digest() {
+        let body = "<Error><Code>BadDigest</Code>\
+                    <Message>The Content-MD5 you specified did not match what we received.</Message>\
+                    </Error>";
+        let error = S3Error::http(400, body.as_bytes());
+        assert_eq!(
+            error,
+            S3Error::BadDigest {
+                status: 400,
+                body: body.to_string()
+            }
+        );
+        assert_eq!(error.status(), Some(400));
+        assert_eq!(error.aws_error().unwrap().code(), "BadDigest");
+    }
+
+    #[test]
+    fn test_other_errors_stay_http() {
+        let body = "<Error><Code>NoSuchKey</Code></Error>";
+        assert_eq!(
+            S3Error::http(404, body.as_bytes()),
+            S3Error::Http {
+                status: 404,
+                body: body.to_string()
+            }
+        );
+    }
+}
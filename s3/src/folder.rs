@@ -0,0 +1,126 @@
+use anyhow::Result;
+#[cfg(feature = "blocking")]
+use block_on_proc::block_on;
+
+use crate::serde_types::ListBucketResult;
+use crate::Bucket;
+
+/// Objects under a key prefix of a bucket, see `Bucket::folder`.
+///
+/// Keys passed to its methods are relative to the prefix, listings return full keys, as stored
+/// in the bucket.
+///
+/// # Example
+///
+/// ```no_run
+/// use s3::bucket::Bucket;
+/// use s3::creds::Credentials;
+/// use anyhow::Result;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// let bucket = Bucket::new("rust-s3-test", "us-east-1".parse()?, Credentials::default()?)?;
+/// let logs = bucket.folder("logs/2024/");
+///
+/// // Puts `logs/2024/app.log`
+/// logs.put_object("app.log", b"started").await?;
+/// let (data, code) = logs.get_object("app.log").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Folder<'a> {
+    bucket: &'a Bucket,
+    prefix: String,
+}
+
+impl<'a> Folder<'a> {
+    pub(crate) fn new(bucket: &'a Bucket, prefix: &str) -> Self {
+        let prefix = prefix.trim_matches('/');
+        Folder {
+            bucket,
+            prefix: if prefix.is_empty() {
+                String::new()
+            } else {
+                format!("{}/", prefix)
+            },
+        }
+    }
+
+    pub fn bucket(&self) -> &'a Bucket {
+        self.bucket
+    }
+
+    /// The prefix, without a leading and with a trailing `/`, empty for the bucket root.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Full key of `key` in the bucket.
+    pub fn key(&self, key: &str) -> String {
+        format!("{}{}", self.prefix, key.trim_start_matches('/'))
+    }
+
+    /// Nested folder, `prefix` is relative to this one.
+    pub fn folder(&self, prefix: &str) -> Folder<'a> {
+        Folder::new(self.bucket, &self.key(prefix))
+    }
+}
+
+#[cfg_attr(all(feature = "with-tokio", feature = "blocking"), block_on("tokio"))]
+#[cfg_attr(
+    all(feature = "with-async-std", feature = "blocking"),
+    block_on("async-std")
+)]
+impl<'a> Folder<'a> {
+    /// `Bucket::get_object` of `key` under the prefix.
+    #[maybe_async::maybe_async]
+    pub async fn get_object(&self, key: &str) -> Result<(Vec<u8>, u16)> {
+        self.bucket.get_object(self.key(key)).await
+    }
+
+    /// `Bucket::put_object` of `key` under the prefix.
+    #[maybe_async::maybe_async]
+    pub async fn put_object(&self, key: &str, content: &[u8]) -> Result<(Vec<u8>, u16)> {
+        self.bucket.put_object(self.key(key), content).await
+    }
+
+    /// `Bucket::delete_object` of `key` under the prefix.
+    #[maybe_async::maybe_async]
+    pub async fn delete_object(&self, key: &str) -> Result<(Vec<u8>, u16)> {
+        self.bucket.delete_object(self.key(key)).await
+    }
+
+    /// `Bucket::list` of everything under the prefix, with `delimiter` `/` only the direct
+    /// children are listed, subfolders show up in `common_prefixes`.
+    #[maybe_async::maybe_async]
+    pub async fn list(&self, delimiter: Option<String>) -> Result<Vec<ListBucketResult>> {
+        self.bucket.list(self.prefix.clone(), delimiter).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::creds::Credentials;
+    use crate::Bucket;
+
+    #[test]
+    fn test_folder_keys() {
+        let bucket = Bucket::new(
+            "rust-s3-test",
+            "us-east-1".parse().unwrap(),
+            Credentials::anonymous().unwrap(),
+        )
+        .unwrap();
+
+        let folder = bucket.folder("/logs/2024");
+        assert_eq!(folder.prefix(), "logs/2024/");
+        assert_eq!(folder.key("app.log"), "logs/2024/app.log");
+        assert_eq!(folder.key("/app.log"), "logs/2024/app.log");
+        assert_eq!(folder.folder("01/").prefix(), "logs/2024/01/");
+
+        let root = bucket.folder("");
+        assert_eq!(root.prefix(), "");
+        assert_eq!(root.key("/test.file"), "test.file");
+    }
+}
@@ -0,0 +1,182 @@
+use crate::serde_types::HeadObjectResult;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Small least recently used cache of successful HEAD results, keyed by object path, with or
+/// without the leading slash.
+///
+/// Clones share the same underlying cache, so a `Bucket` cloned into worker threads or tasks
+/// keeps benefiting from, and invalidating, the same entries.
+#[derive(Clone)]
+pub struct HeadCache {
+    capacity: usize,
+    ttl: Duration,
+    inner: Arc<Mutex<Entries>>,
+}
+
+#[derive(Default)]
+struct Entries {
+    map: HashMap<String, (Instant, HeadObjectResult)>,
+    // Least recently used key first
+    order: VecDeque<String>,
+}
+
+impl Entries {
+    fn touch(&mut self, key: &str) {
+        if let Some(position) = self.order.iter().position(|k| k == key) {
+            if let Some(key) = self.order.remove(position) {
+                self.order.push_back(key);
+            }
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.map.remove(key);
+        self.order.retain(|k| k != key);
+    }
+}
+
+/// Object paths are accepted with and without the leading slash, like in requests.
+fn cache_key(path: &str) -> &str {
+    path.trim_start_matches('/')
+}
+
+impl HeadCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        HeadCache {
+            capacity,
+            ttl,
+            inner: Arc::new(Mutex::new(Entries::default())),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Cached result for `key`, if present and younger than the TTL.
+    pub fn get(&self, key: &str) -> Option<HeadObjectResult> {
+        let key = cache_key(key);
+        let mut entries = self.inner.lock().unwrap();
+        let expired = match entries.map.get(key) {
+            Some((inserted, _)) => inserted.elapsed() > self.ttl,
+            None => return None,
+        };
+        if expired {
+            entries.remove(key);
+            return None;
+        }
+        entries.touch(key);
+        entries.map.get(key).map(|(_, result)| result.clone())
+    }
+
+    /// Store `result` for `key`, evicting the least recently used entry when full.
+    pub fn insert(&self, key: &str, result: HeadObjectResult) {
+        if self.capacity == 0 {
+            return;
+        }
+        let key = cache_key(key);
+        let mut entries = self.inner.lock().unwrap();
+        if entries.map.contains_key(key) {
+            entries.touch(key);
+        } else {
+            while entries.order.len() >= self.capacity {
+                match entries.order.pop_front() {
+                    Some(oldest) => {
+                        entries.map.remove(&oldest);
+                    }
+                    None => break,
+                }
+            }
+            entries.order.push_back(key.to_string());
+        }
+        entries
+            .map
+            .insert(key.to_string(), (Instant::now(), result));
+    }
+
+    pub fn invalidate(&self, key: &str) {
+        self.inner.lock().unwrap().remove(cache_key(key));
+    }
+
+    pub fn clear(&self) {
+        let mut entries = self.inner.lock().unwrap();
+        entries.map.clear();
+        entries.order.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl fmt::Debug for HeadCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HeadCache")
+            .field("capacity", &self.capacity)
+            .field("ttl", &self.ttl)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// Caches are equal when they share the same underlying entries.
+impl PartialEq for HeadCache {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Eq for HeadCache {}
+
+#[cfg(test)]
+mod test {
+    use super::HeadCache;
+    use crate::serde_types::HeadObjectResult;
+    use std::time::Duration;
+
+    fn result(e_tag: &str) -> HeadObjectResult {
+        HeadObjectResult {
+            etag: Some(e_tag.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_head_cache_evicts_least_recently_used() {
+        let cache = HeadCache::new(2, Duration::from_secs(60));
+        cache.insert("a", result("a"));
+        cache.insert("b", result("b"));
+        assert!(cache.get("a").is_some());
+        cache.insert("c", result("c"));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get("b").is_none());
+        assert_eq!(cache.get("a").unwrap().etag.as_deref(), Some("a"));
+        assert_eq!(cache.get("c").unwrap().etag.as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn test_head_cache_ttl_and_invalidate() {
+        let cache = HeadCache::new(2, Duration::from_secs(0));
+        cache.insert("a", result("a"));
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(cache.get("a").is_none());
+        assert!(cache.is_empty());
+
+        let cache = HeadCache::new(2, Duration::from_secs(60));
+        let shared = cache.clone();
+        cache.insert("a", result("a"));
+        shared.invalidate("a");
+        assert!(cache.get("a").is_none());
+    }
+}
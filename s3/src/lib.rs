@@ -1,4 +1,38 @@
 //! Simple access to Amazon Web Service's (AWS) Simple Storage Service (S3)
+//!
+//! `Bucket` methods are `async fn`s returning `std::future::Future`s, backed by `reqwest` on
+//! tokio 1.x (`with-tokio`, the default) or by `surf` on async-std (`with-async-std`). The `sync`
+//! feature turns the same methods into plain blocking calls, while `blocking` adds `*_blocking`
+//! variants next to the async ones.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use s3::bucket::Bucket;
+//! use s3::creds::Credentials;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! let bucket = Bucket::new("rust-s3-test", "us-east-1".parse()?, Credentials::default()?)?;
+//!
+//! let (_, code) = bucket.put_object("/test.file", b"I want to go to S3").await?;
+//! assert_eq!(code, 200);
+//!
+//! let (data, code) = bucket.get_object("/test.file").await?;
+//! assert_eq!(code, 200);
+//! assert_eq!(data, b"I want to go to S3");
+//!
+//! for page in bucket.list("/".to_string(), None).await? {
+//!     for object in page.contents {
+//!         println!("{}", object.key);
+//!     }
+//! }
+//!
+//! let (_, code) = bucket.delete_object("/test.file").await?;
+//! assert_eq!(code, 204);
+//! # Ok(())
+//! # }
+//! ```
 #![forbid(unsafe_code)]
 
 #[macro_use]
@@ -8,9 +42,14 @@ pub use awscreds as creds;
 pub use awsregion as region;
 
 pub use bucket::Bucket;
-pub use bucket::Tag;
+pub use bucket::{PresignedUrl, Tag};
 pub use bucket_ops::BucketConfiguration;
+pub use error::S3Error;
+pub use folder::Folder;
+pub use multipart::MultipartUpload;
+pub use object_ops::{CopyObjectOptions, PutObjectOptions, Sse};
 pub use region::Region;
+pub use serde_types::AwsError;
 
 #[cfg(feature = "sync")]
 pub mod blocking;
@@ -18,8 +57,14 @@ pub mod bucket;
 pub mod bucket_ops;
 pub mod command;
 pub mod deserializer;
+pub mod error;
+pub mod folder;
+pub mod head_cache;
+pub mod multipart;
+pub mod object_ops;
 #[cfg(feature = "with-tokio")]
 pub mod request;
+pub mod select;
 pub mod serde_types;
 pub mod signing;
 #[cfg(feature = "with-async-std")]
@@ -29,4 +74,6 @@ pub mod request_trait;
 pub mod utils;
 
 const LONG_DATE: &str = "%Y%m%dT%H%M%SZ";
+/// RFC 1123 date, as used by HTTP headers like `Date` and `Expires`
+const HTTP_DATE: &str = "%a, %d %b %Y %H:%M:%S GMT";
 const EMPTY_PAYLOAD_SHA: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
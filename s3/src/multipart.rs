@@ -0,0 +1,173 @@
+use anyhow::Result;
+#[cfg(feature = "blocking")]
+use block_on_proc::block_on;
+
+use crate::object_ops::ChecksumAlgorithm;
+use crate::serde_types::Part;
+use crate::Bucket;
+
+/// A multipart upload in progress, see `Bucket::initiate_multipart_upload`.
+///
+/// Parts of an upload that is neither completed nor aborted are kept, and billed, until a
+/// lifecycle rule cleans them up. Dropping the handle without calling `complete` or `abort`
+/// therefore aborts the upload, best-effort: a failed abort is logged as a warning, and with
+/// `with-tokio` the abort is only spawned when dropped inside a tokio runtime, outside of one it
+/// is skipped with a warning. Call `into_upload_id` to keep the upload going past the handle,
+/// e.g. to resume it later with `Bucket::put_part_with_checksum` and
+/// `Bucket::complete_multipart_upload`.
+///
+/// # Example
+///
+/// ```no_run
+/// use s3::bucket::Bucket;
+/// use s3::creds::Credentials;
+/// use anyhow::Result;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// let bucket = Bucket::new("rust-s3-test", "us-east-1".parse()?, Credentials::default()?)?;
+///
+/// let upload = bucket.initiate_multipart_upload("/big.file", None).await?;
+/// // Returning early here, on error, aborts the upload
+/// let part = upload.put_part(1, b"last part", true).await?;
+/// let code = upload.complete(vec![part]).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MultipartUpload {
+    bucket: Bucket,
+    key: String,
+    upload_id: String,
+    checksum_algorithm: Option<ChecksumAlgorithm>,
+    finished: bool,
+}
+
+impl MultipartUpload {
+    pub(crate) fn new(
+        bucket: &Bucket,
+        key: String,
+        upload_id: String,
+        checksum_algorithm: Option<ChecksumAlgorithm>,
+    ) -> Self {
+        MultipartUpload {
+            bucket: bucket.clone(),
+            key,
+            upload_id,
+            checksum_algorithm,
+            finished: false,
+        }
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn upload_id(&self) -> &str {
+        &self.upload_id
+    }
+
+    /// Detach the handle from the upload, returning its id, the upload is no longer aborted on
+    /// drop and has to be completed or aborted through `Bucket`.
+    pub fn into_upload_id(mut self) -> String {
+        self.finished = true;
+        std::mem::take(&mut self.upload_id)
+    }
+
+    /// Algorithm the upload was initiated with, parts are sent with a checksum computed with it.
+    pub fn checksum_algorithm(&self) -> Option<ChecksumAlgorithm> {
+        self.checksum_algorithm
+    }
+}
+
+#[cfg_attr(all(feature = "with-tokio", feature = "blocking"), block_on("tokio"))]
+#[cfg_attr(
+    all(feature = "with-async-std", feature = "blocking"),
+    block_on("async-std")
+)]
+impl MultipartUpload {
+    /// `Bucket::put_part_with_checksum` of part `part_number` of this upload.
+    #[maybe_async::maybe_async]
+    pub async fn put_part(&self, part_number: u32, content: &[u8], last: bool) -> Result<Part> {
+        self.bucket
+            .put_part_with_checksum(
+                &self.key,
+                &self.upload_id,
+                part_number,
+                content,
+                last,
+                self.checksum_algorithm,
+            )
+            .await
+    }
+
+    /// `Bucket::complete_multipart_upload` from `parts`, the upload is aborted on drop if this
+    /// fails.
+    #[maybe_async::maybe_async]
+    pub async fn complete(mut self, parts: Vec<Part>) -> Result<u16> {
+        let code = self
+            .bucket
+            .complete_multipart_upload(&self.key, &self.upload_id, parts)
+            .await?;
+        self.finished = true;
+        Ok(code)
+    }
+
+    /// `Bucket::abort_upload` of this upload, which is not retried on drop if it fails.
+    #[maybe_async::maybe_async]
+    pub async fn abort(mut self) -> Result<()> {
+        self.finished = true;
+        self.bucket.abort_upload(&self.key, &self.upload_id).await
+    }
+}
+
+impl Drop for MultipartUpload {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        let bucket = self.bucket.clone();
+        let key = std::mem::take(&mut self.key);
+        let upload_id = std::mem::take(&mut self.upload_id);
+
+        #[cfg(feature = "sync")]
+        {
+            let result = bucket.abort_upload(&key, &upload_id);
+            log_abort_failure(&key, &upload_id, result);
+        }
+        #[cfg(all(not(feature = "sync"), feature = "with-tokio"))]
+        {
+            match tokio::runtime::Handle::try_current() {
+                Ok(handle) => {
+                    handle.spawn(async move {
+                        let result = bucket.abort_upload(&key, &upload_id).await;
+                        log_abort_failure(&key, &upload_id, result);
+                    });
+                }
+                Err(_) => log::warn!(
+                    "Multipart upload {} of {} dropped outside of a tokio runtime, not aborted",
+                    upload_id,
+                    key
+                ),
+            }
+        }
+        #[cfg(all(not(feature = "sync"), feature = "with-async-std"))]
+        {
+            async_std::task::spawn(async move {
+                let result = bucket.abort_upload(&key, &upload_id).await;
+                log_abort_failure(&key, &upload_id, result);
+            });
+        }
+    }
+}
+
+fn log_abort_failure(key: &str, upload_id: &str, result: Result<()>) {
+    if let Err(e) = result {
+        log::warn!(
+            "Aborting dropped multipart upload {} of {} failed: {}",
+            upload_id,
+            key,
+            e
+        );
+    }
+}
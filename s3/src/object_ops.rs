@@ -0,0 +1,1407 @@
+use anyhow::anyhow;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use http::header::{
+    HeaderName, CACHE_CONTROL, CONTENT_DISPOSITION, CONTENT_ENCODING, CONTENT_LANGUAGE, EXPIRES,
+};
+use http::HeaderMap;
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+use crate::deserializer::parse_http_date;
+use crate::error::S3Error;
+pub use crate::serde_types::{Grantee, Permission};
+use crate::serde_types::{HeadObjectResult, StorageClass};
+use crate::utils::{crc32_update, crc32c_update};
+use crate::HTTP_DATE;
+
+/// Max number of tags S3 allows on a single object
+pub const MAX_TAGS: usize = 10;
+/// Max length of a tag key, in Unicode characters
+pub const MAX_TAG_KEY_LEN: usize = 128;
+/// Max length of a tag value, in Unicode characters
+pub const MAX_TAG_VALUE_LEN: usize = 256;
+
+fn is_valid_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || c == ' ' || "+-=._:/@".contains(c)
+}
+
+/// [AWS Documentation](https://docs.aws.amazon.com/AmazonS3/latest/dev/object-tagging.html)
+///
+/// Check tags against the limits S3 enforces: at most `MAX_TAGS` tags with unique keys, keys of
+/// 1 to `MAX_TAG_KEY_LEN` characters not starting with `aws:`, values of at most
+/// `MAX_TAG_VALUE_LEN` characters, made up of letters, digits, spaces and `+ - = . _ : / @`.
+pub fn validate_tags<S: AsRef<str>>(tags: &[(S, S)]) -> Result<()> {
+    if tags.len() > MAX_TAGS {
+        return Err(anyhow!(
+            "Objects can have at most {} tags, got {}",
+            MAX_TAGS,
+            tags.len()
+        ));
+    }
+    for (i, (key, value)) in tags.iter().enumerate() {
+        let (key, value) = (key.as_ref(), value.as_ref());
+        let key_len = key.chars().count();
+        if key_len == 0 || key_len > MAX_TAG_KEY_LEN {
+            return Err(anyhow!(
+                "Tag keys must be 1 to {} characters long, got {:?}",
+                MAX_TAG_KEY_LEN,
+                key
+            ));
+        }
+        if value.chars().count() > MAX_TAG_VALUE_LEN {
+            return Err(anyhow!(
+                "Tag values can be at most {} characters long, got {:?}",
+                MAX_TAG_VALUE_LEN,
+                value
+            ));
+        }
+        if key.starts_with("aws:") {
+            return Err(anyhow!(
+                "Tag keys starting with aws: are reserved, got {:?}",
+                key
+            ));
+        }
+        if !key.chars().chain(value.chars()).all(is_valid_tag_char) {
+            return Err(anyhow!(
+                "Tags can only contain letters, digits, spaces and + - = . _ : / @, got {:?}={:?}",
+                key,
+                value
+            ));
+        }
+        if tags[..i].iter().any(|(other, _)| other.as_ref() == key) {
+            return Err(anyhow!("Duplicate tag key {:?}", key));
+        }
+    }
+    Ok(())
+}
+
+fn add_metadata_headers(metadata: &[(String, String)], headers: &mut HeaderMap) -> Result<()> {
+    for (key, value) in metadata {
+        let name = HeaderName::from_bytes(format!("x-amz-meta-{}", key).to_lowercase().as_bytes())
+            .map_err(|_| anyhow!("Invalid metadata key {:?}", key))?;
+        headers.insert(name, value.parse()?);
+    }
+    Ok(())
+}
+
+impl Permission {
+    fn header(&self) -> HeaderName {
+        HeaderName::from_static(match self {
+            Permission::Read => "x-amz-grant-read",
+            Permission::Write => "x-amz-grant-write",
+            Permission::ReadAcp => "x-amz-grant-read-acp",
+            Permission::WriteAcp => "x-amz-grant-write-acp",
+            Permission::FullControl => "x-amz-grant-full-control",
+        })
+    }
+}
+
+impl Grantee {
+    /// [AWS Documentation](https://docs.aws.amazon.com/AmazonS3/latest/API/API_PutObject.html#API_PutObject_RequestSyntax)
+    ///
+    /// Grantee as listed in the `x-amz-grant-*` headers.
+    fn header_value(&self) -> String {
+        match self {
+            Grantee::CanonicalUser { id, .. } => format!("id=\"{}\"", id),
+            Grantee::Group(uri) => format!("uri=\"{}\"", uri),
+            Grantee::Email(email) => format!("emailAddress=\"{}\"", email),
+        }
+    }
+}
+
+/// [AWS Documentation](https://docs.aws.amazon.com/AmazonS3/latest/userguide/checking-object-integrity.html)
+///
+/// Algorithm of the additional checksum S3 verifies uploaded content against, on top of the
+/// `Content-MD5` sent with every upload. For multipart uploads, the checksum of every part is
+/// sent along with the part, and listed again on completion, so that S3 can verify the
+/// assembled object, see `Bucket::put_part_with_checksum`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    Crc32,
+    Crc32c,
+    Sha256,
+}
+
+impl ChecksumAlgorithm {
+    /// Header carrying the checksum of the content.
+    pub(crate) fn header(&self) -> HeaderName {
+        HeaderName::from_static(match self {
+            ChecksumAlgorithm::Crc32 => "x-amz-checksum-crc32",
+            ChecksumAlgorithm::Crc32c => "x-amz-checksum-crc32c",
+            ChecksumAlgorithm::Sha256 => "x-amz-checksum-sha256",
+        })
+    }
+
+    /// Base64 encoded checksum of `content`, the form S3 expects in headers and `<Part>`s.
+    pub fn checksum(&self, content: &[u8]) -> String {
+        let mut checksum = self.hasher();
+        checksum.update(content);
+        checksum.finish()
+    }
+
+    /// Checksum of content that is fed to it piece by piece.
+    pub(crate) fn hasher(&self) -> Checksum {
+        match self {
+            ChecksumAlgorithm::Crc32 => Checksum::Crc32(0),
+            ChecksumAlgorithm::Crc32c => Checksum::Crc32c(0),
+            ChecksumAlgorithm::Sha256 => Checksum::Sha256(Sha256::default()),
+        }
+    }
+}
+
+/// Checksum of the content so far, see `ChecksumAlgorithm::hasher`.
+#[derive(Clone)]
+pub(crate) enum Checksum {
+    Crc32(u32),
+    Crc32c(u32),
+    Sha256(Sha256),
+}
+
+impl Checksum {
+    pub(crate) fn update(&mut self, content: &[u8]) {
+        match self {
+            Checksum::Crc32(crc) => *crc = crc32_update(*crc, content),
+            Checksum::Crc32c(crc) => *crc = crc32c_update(*crc, content),
+            Checksum::Sha256(sha) => sha.update(content),
+        }
+    }
+
+    /// Base64 encoded checksum, as in `ChecksumAlgorithm::checksum`.
+    pub(crate) fn finish(self) -> String {
+        match self {
+            Checksum::Crc32(crc) | Checksum::Crc32c(crc) => base64::encode(crc.to_be_bytes()),
+            Checksum::Sha256(sha) => base64::encode(sha.finalize()),
+        }
+    }
+}
+
+impl fmt::Display for ChecksumAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChecksumAlgorithm::Crc32 => write!(f, "CRC32"),
+            ChecksumAlgorithm::Crc32c => write!(f, "CRC32C"),
+            ChecksumAlgorithm::Sha256 => write!(f, "SHA256"),
+        }
+    }
+}
+
+/// Size of the chunks of an `aws-chunked` body, as used by the AWS SDKs.
+pub const AWS_CHUNK_SIZE: usize = 65_536;
+
+/// Encodes content into the pieces of an `aws-chunked` body as it is read, with the checksum of
+/// the content as a trailer after the last chunk, see `Bucket::put_object_with_trailing_checksum`.
+/// The content is gone through once and at most a chunk of it is held in memory.
+pub(crate) struct AwsChunkedEncoder {
+    /// Length of the content, sent as `x-amz-decoded-content-length` before the body.
+    decoded_length: u64,
+    read: u64,
+    /// Start of the next chunk, until there is `AWS_CHUNK_SIZE` of it or the content ends.
+    pending: Vec<u8>,
+    algorithm: ChecksumAlgorithm,
+    checksum: Option<Checksum>,
+}
+
+impl AwsChunkedEncoder {
+    pub(crate) fn new(decoded_length: u64, algorithm: ChecksumAlgorithm) -> Self {
+        AwsChunkedEncoder {
+            decoded_length,
+            read: 0,
+            pending: Vec::with_capacity(AWS_CHUNK_SIZE),
+            algorithm,
+            checksum: Some(algorithm.hasher()),
+        }
+    }
+
+    /// Length of the body encoding `decoded_length` bytes of content, sent as `Content-Length`,
+    /// as S3 does not take `Transfer-Encoding: chunked`.
+    pub(crate) fn encoded_length(decoded_length: u64, algorithm: ChecksumAlgorithm) -> u64 {
+        let chunk_size = AWS_CHUNK_SIZE as u64;
+        let chunk = |len: u64| format!("{:x}\r\n\r\n", len).len() as u64 + len;
+        let rest = match decoded_length % chunk_size {
+            0 => 0,
+            rest => chunk(rest),
+        };
+        // The checksum is as long for any content
+        let trailer = format!(
+            "0\r\n{}:{}\r\n\r\n",
+            algorithm.header(),
+            algorithm.checksum(b"")
+        );
+        decoded_length / chunk_size * chunk(chunk_size) + rest + trailer.len() as u64
+    }
+
+    /// Whether the trailer has been produced, ending the body.
+    pub(crate) fn is_finished(&self) -> bool {
+        self.checksum.is_none()
+    }
+
+    /// Encode the next `content` that was read into the chunks it completes, so every chunk but
+    /// the last is `AWS_CHUNK_SIZE` long. Empty `content` marks the end of the content, and is
+    /// encoded as the last chunks and the checksum trailer. Content that does not add up to the
+    /// announced length is an error, as the `Content-Length` of the request would be wrong.
+    pub(crate) fn encode(&mut self, content: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut checksum = match self.checksum.take() {
+            Some(checksum) => checksum,
+            None => return Ok(Vec::new()),
+        };
+        let end = content.is_empty();
+        self.read += content.len() as u64;
+        if self.read > self.decoded_length || (end && self.read < self.decoded_length) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "Read {} bytes of content, expected {}",
+                    self.read, self.decoded_length
+                ),
+            ));
+        }
+
+        let mut body = Vec::new();
+        let mut chunk = |content: &[u8]| {
+            body.extend_from_slice(format!("{:x}\r\n", content.len()).as_bytes());
+            body.extend_from_slice(content);
+            body.extend_from_slice(b"\r\n");
+            checksum.update(content);
+        };
+        let mut content = content;
+        if !self.pending.is_empty() {
+            let missing = (AWS_CHUNK_SIZE - self.pending.len()).min(content.len());
+            self.pending.extend_from_slice(&content[..missing]);
+            content = &content[missing..];
+            if self.pending.len() == AWS_CHUNK_SIZE {
+                chunk(&self.pending);
+                self.pending.clear();
+            }
+        }
+        let mut chunks = content.chunks_exact(AWS_CHUNK_SIZE);
+        for full in &mut chunks {
+            chunk(full);
+        }
+        self.pending.extend_from_slice(chunks.remainder());
+
+        if end {
+            if !self.pending.is_empty() {
+                chunk(&self.pending);
+                self.pending.clear();
+            }
+            body.extend_from_slice(
+                format!(
+                    "0\r\n{}:{}\r\n\r\n",
+                    self.algorithm.header(),
+                    checksum.finish()
+                )
+                .as_bytes(),
+            );
+        } else {
+            self.checksum = Some(checksum);
+        }
+        Ok(body)
+    }
+}
+
+/// [AWS Documentation](https://docs.aws.amazon.com/AmazonS3/latest/userguide/serv-side-encryption.html)
+///
+/// Server-side encryption to request for an upload, see `PutObjectOptions::with_sse`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Sse {
+    /// Keys managed by S3, SSE-S3.
+    Aes256,
+    /// The AWS KMS key with this id or ARN, SSE-KMS.
+    Kms(String),
+    /// A 256 bit key provided with the request, SSE-C. S3 does not store the key, reading the
+    /// object back requires it too.
+    CustomerKey {
+        key_b64: String,
+        key_md5_b64: String,
+    },
+}
+
+impl Sse {
+    /// SSE-C with the raw 256 bit `key`, base64 encoded along with its MD5 as S3 expects.
+    pub fn customer_key(key: &[u8]) -> Self {
+        Sse::CustomerKey {
+            key_b64: base64::encode(key),
+            key_md5_b64: base64::encode(md5::compute(key).as_ref()),
+        }
+    }
+
+    pub fn add_headers(&self, headers: &mut HeaderMap) -> Result<()> {
+        match self {
+            Sse::Aes256 => {
+                headers.insert(
+                    HeaderName::from_static("x-amz-server-side-encryption"),
+                    "AES256".parse()?,
+                );
+            }
+            Sse::Kms(key_id) => {
+                headers.insert(
+                    HeaderName::from_static("x-amz-server-side-encryption"),
+                    "aws:kms".parse()?,
+                );
+                headers.insert(
+                    HeaderName::from_static("x-amz-server-side-encryption-aws-kms-key-id"),
+                    key_id.parse()?,
+                );
+            }
+            Sse::CustomerKey {
+                key_b64,
+                key_md5_b64,
+            } => {
+                headers.insert(
+                    HeaderName::from_static("x-amz-server-side-encryption-customer-algorithm"),
+                    "AES256".parse()?,
+                );
+                headers.insert(
+                    HeaderName::from_static("x-amz-server-side-encryption-customer-key"),
+                    key_b64.parse()?,
+                );
+                headers.insert(
+                    HeaderName::from_static("x-amz-server-side-encryption-customer-key-md5"),
+                    key_md5_b64.parse()?,
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Optional settings for a single object upload, sent as (signed) request headers.
+///
+/// # Example
+///
+/// ```
+/// use s3::PutObjectOptions;
+///
+/// // Turn the uploaded object into a redirect when served from a static website bucket
+/// let options = PutObjectOptions::new().with_website_redirect_location("/new/location.html");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PutObjectOptions {
+    website_redirect_location: Option<String>,
+    tags: Option<Vec<(String, String)>>,
+    metadata: Vec<(String, String)>,
+    storage_class: Option<StorageClass>,
+    grants: Vec<(Permission, Grantee)>,
+    skip_if_unchanged: bool,
+    cache_control: Option<String>,
+    content_disposition: Option<String>,
+    content_encoding: Option<String>,
+    content_language: Option<String>,
+    expires: Option<DateTime<Utc>>,
+    checksum_algorithm: Option<ChecksumAlgorithm>,
+    sse: Option<Sse>,
+}
+
+impl PutObjectOptions {
+    pub fn new() -> Self {
+        PutObjectOptions::default()
+    }
+
+    /// [AWS Documentation](https://docs.aws.amazon.com/AmazonS3/latest/dev/how-to-page-redirect.html)
+    ///
+    /// Redirect requests for the object to another object in the same bucket (`/path`) or to an
+    /// external URL (`http://` or `https://`), when the bucket is configured as a website.
+    pub fn with_website_redirect_location(mut self, location: &str) -> Self {
+        self.website_redirect_location = Some(location.to_string());
+        self
+    }
+
+    pub fn website_redirect_location(&self) -> Option<&str> {
+        self.website_redirect_location.as_deref()
+    }
+
+    /// Tag the object on upload, see `validate_tags` for the limits S3 enforces.
+    pub fn with_tags<S: AsRef<str>>(mut self, tags: &[(S, S)]) -> Self {
+        self.tags = Some(
+            tags.iter()
+                .map(|(key, value)| (key.as_ref().to_string(), value.as_ref().to_string()))
+                .collect(),
+        );
+        self
+    }
+
+    pub fn tags(&self) -> Option<&[(String, String)]> {
+        self.tags.as_deref()
+    }
+
+    /// User metadata, stored as `x-amz-meta-<key>` headers and returned by `head_object`.
+    pub fn with_metadata<S: AsRef<str>>(mut self, metadata: &[(S, S)]) -> Self {
+        self.metadata = metadata
+            .iter()
+            .map(|(key, value)| (key.as_ref().to_string(), value.as_ref().to_string()))
+            .collect();
+        self
+    }
+
+    pub fn metadata(&self) -> &[(String, String)] {
+        &self.metadata
+    }
+
+    /// Store the object in `storage_class` rather than `STANDARD`.
+    pub fn with_storage_class(mut self, storage_class: StorageClass) -> Self {
+        self.storage_class = Some(storage_class);
+        self
+    }
+
+    pub fn storage_class(&self) -> Option<&StorageClass> {
+        self.storage_class.as_ref()
+    }
+
+    /// Explicitly grant `permission` on the object to `grantee`, can be called repeatedly.
+    /// `Permission::Write` only applies to buckets, S3 has no `x-amz-grant-write` for objects,
+    /// so uploads with such a grant fail before being sent.
+    pub fn with_grant(mut self, permission: Permission, grantee: Grantee) -> Self {
+        self.grants.push((permission, grantee));
+        self
+    }
+
+    pub fn grants(&self) -> &[(Permission, Grantee)] {
+        &self.grants
+    }
+
+    /// HEAD the object first and skip the upload if its ETag is the MD5 of the content, and
+    /// its content type, user metadata and the other headers these options set are the same,
+    /// making retried puts of the same content cheap. A missing object is uploaded.
+    ///
+    /// Only objects uploaded with a single put and without SSE-KMS or SSE-C can be skipped, the
+    /// ETags of multipart uploads and of such encrypted objects are not content MD5s, so they
+    /// are always uploaded again. Tags, grants and SSE can't be compared with a HEAD, options
+    /// setting them fail the put rather than skip changes to them.
+    pub fn with_skip_if_unchanged(mut self, skip_if_unchanged: bool) -> Self {
+        self.skip_if_unchanged = skip_if_unchanged;
+        self
+    }
+
+    pub fn skip_if_unchanged(&self) -> bool {
+        self.skip_if_unchanged
+    }
+
+    /// Fail if `skip_if_unchanged` is combined with attributes a HEAD doesn't show.
+    pub(crate) fn validate_skip_if_unchanged(&self) -> Result<()> {
+        if self.skip_if_unchanged
+            && (self.tags.is_some() || !self.grants.is_empty() || self.sse.is_some())
+        {
+            return Err(anyhow!(
+                "skip_if_unchanged can't be combined with tags, grants or SSE"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether the existing object described by `head` already has `content`, `content_type`
+    /// and the attributes these options set, see `with_skip_if_unchanged`.
+    pub(crate) fn is_unchanged(
+        &self,
+        head: &HeadObjectResult,
+        content: &[u8],
+        content_type: &str,
+    ) -> bool {
+        let md5 = format!("{:x}", md5::compute(content));
+        let standard = StorageClass::Standard;
+        head.etag.as_deref().map(|etag| etag.trim_matches('"')) == Some(md5.as_str())
+            && head.content_type.as_deref() == Some(content_type)
+            && head.metadata.len() == self.metadata.len()
+            && self.metadata.iter().all(|(key, value)| {
+                head.metadata.get(&key.to_ascii_lowercase()) == Some(value)
+            })
+            && head.cache_control.as_deref() == self.cache_control()
+            && head.content_disposition.as_deref() == self.content_disposition()
+            && head.content_encoding.as_deref() == self.content_encoding()
+            && head.content_language.as_deref() == self.content_language()
+            && head.expires.as_deref().and_then(parse_http_date) == self.expires
+            && head.website_redirect_location.as_deref() == self.website_redirect_location()
+            // S3 leaves out the storage class of STANDARD objects
+            && head.storage_class.as_ref().unwrap_or(&standard)
+                == self.storage_class().unwrap_or(&standard)
+    }
+
+    /// `Cache-Control` to serve the object with, e.g. `max-age=3600`.
+    pub fn with_cache_control(mut self, cache_control: &str) -> Self {
+        self.cache_control = Some(cache_control.to_string());
+        self
+    }
+
+    pub fn cache_control(&self) -> Option<&str> {
+        self.cache_control.as_deref()
+    }
+
+    /// `Content-Disposition` to serve the object with, e.g. `attachment; filename="report.pdf"`.
+    pub fn with_content_disposition(mut self, content_disposition: &str) -> Self {
+        self.content_disposition = Some(content_disposition.to_string());
+        self
+    }
+
+    pub fn content_disposition(&self) -> Option<&str> {
+        self.content_disposition.as_deref()
+    }
+
+    /// `Content-Encoding` of the content, e.g. `gzip` for content compressed before the upload.
+    pub fn with_content_encoding(mut self, content_encoding: &str) -> Self {
+        self.content_encoding = Some(content_encoding.to_string());
+        self
+    }
+
+    pub fn content_encoding(&self) -> Option<&str> {
+        self.content_encoding.as_deref()
+    }
+
+    /// `Content-Language` of the content, e.g. `de-CH`.
+    pub fn with_content_language(mut self, content_language: &str) -> Self {
+        self.content_language = Some(content_language.to_string());
+        self
+    }
+
+    pub fn content_language(&self) -> Option<&str> {
+        self.content_language.as_deref()
+    }
+
+    /// `Expires` to serve the object with, when caches should consider it stale.
+    pub fn with_expires(mut self, expires: DateTime<Utc>) -> Self {
+        self.expires = Some(expires);
+        self
+    }
+
+    pub fn expires(&self) -> Option<DateTime<Utc>> {
+        self.expires
+    }
+
+    /// Send a checksum of the content, computed with `algorithm`, for S3 to verify.
+    pub fn with_checksum_algorithm(mut self, algorithm: ChecksumAlgorithm) -> Self {
+        self.checksum_algorithm = Some(algorithm);
+        self
+    }
+
+    pub fn checksum_algorithm(&self) -> Option<ChecksumAlgorithm> {
+        self.checksum_algorithm
+    }
+
+    /// Have S3 encrypt the stored object, see `Sse`.
+    pub fn with_sse(mut self, sse: Sse) -> Self {
+        self.sse = Some(sse);
+        self
+    }
+
+    pub fn sse(&self) -> Option<&Sse> {
+        self.sse.as_ref()
+    }
+
+    pub fn add_headers(&self, headers: &mut HeaderMap) -> Result<()> {
+        for (name, value) in &[
+            (CACHE_CONTROL, &self.cache_control),
+            (CONTENT_DISPOSITION, &self.content_disposition),
+            (CONTENT_ENCODING, &self.content_encoding),
+            (CONTENT_LANGUAGE, &self.content_language),
+        ] {
+            if let Some(value) = value {
+                headers.insert(name.clone(), value.parse()?);
+            }
+        }
+        if let Some(expires) = self.expires {
+            headers.insert(EXPIRES, expires.format(HTTP_DATE).to_string().parse()?);
+        }
+        if let Some(ref sse) = self.sse {
+            sse.add_headers(headers)?;
+        }
+        if let Some(ref storage_class) = self.storage_class {
+            headers.insert(
+                HeaderName::from_static("x-amz-storage-class"),
+                storage_class.to_string().parse()?,
+            );
+        }
+        if let Some(ref location) = self.website_redirect_location {
+            if !(location.starts_with('/')
+                || location.starts_with("http://")
+                || location.starts_with("https://"))
+            {
+                return Err(anyhow!(
+                    "Website redirect location must start with /, http:// or https://, got {}",
+                    location
+                ));
+            }
+            headers.insert(
+                HeaderName::from_static("x-amz-website-redirect-location"),
+                location.parse()?,
+            );
+        }
+        if let Some(ref tags) = self.tags {
+            validate_tags(tags)?;
+            let tagging = url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(tags)
+                .finish();
+            headers.insert(HeaderName::from_static("x-amz-tagging"), tagging.parse()?);
+        }
+        if self
+            .grants
+            .iter()
+            .any(|(permission, _)| *permission == Permission::Write)
+        {
+            return Err(anyhow!(
+                "WRITE can only be granted on buckets, not on objects"
+            ));
+        }
+        for permission in &[
+            Permission::Read,
+            Permission::ReadAcp,
+            Permission::WriteAcp,
+            Permission::FullControl,
+        ] {
+            let grantees = self
+                .grants
+                .iter()
+                .filter(|(p, _)| p == permission)
+                .map(|(_, grantee)| grantee.header_value())
+                .collect::<Vec<String>>();
+            if !grantees.is_empty() {
+                headers.insert(permission.header(), grantees.join(", ").parse()?);
+            }
+        }
+        add_metadata_headers(&self.metadata, headers)
+    }
+}
+
+/// Smallest part S3 accepts in a multipart upload, except for the last one
+pub const MIN_PART_SIZE: usize = 5_242_880;
+/// Max number of parts of a multipart upload
+pub const MAX_PARTS: u64 = 10_000;
+
+/// Check that `part_number` is between 1 and `MAX_PARTS`.
+pub fn validate_part_number(part_number: u32) -> Result<()> {
+    if part_number == 0 || u64::from(part_number) > MAX_PARTS {
+        return Err(S3Error::InvalidPartNumber { part_number }.into());
+    }
+    Ok(())
+}
+
+/// Check a multipart upload part before sending it: part numbers run from 1 to `MAX_PARTS`, and
+/// all parts but the `last` one have to be at least `MIN_PART_SIZE` bytes.
+pub fn validate_part(part_number: u32, size: usize, last: bool) -> Result<()> {
+    validate_part_number(part_number)?;
+    if !last && size < MIN_PART_SIZE {
+        return Err(anyhow!(
+            "Part {} is {} bytes, all parts but the last must be at least {} bytes",
+            part_number,
+            size,
+            MIN_PART_SIZE
+        ));
+    }
+    Ok(())
+}
+
+/// Tuning of `Bucket::upload_file`.
+///
+/// Files smaller than `threshold` bytes are uploaded with a single put, larger files with a
+/// multipart upload of `part_size` byte parts, `concurrency` of them in flight at a time. Memory
+/// use is roughly `part_size * concurrency`.
+///
+/// # Example
+///
+/// ```
+/// use s3::object_ops::UploadConfig;
+///
+/// // Bigger parts, more of them at once, for a fast link
+/// let config = UploadConfig {
+///     part_size: 64 * 1024 * 1024,
+///     concurrency: 8,
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UploadConfig {
+    pub threshold: usize,
+    pub part_size: usize,
+    pub concurrency: usize,
+}
+
+impl Default for UploadConfig {
+    fn default() -> Self {
+        UploadConfig {
+            threshold: crate::bucket::CHUNK_SIZE,
+            part_size: crate::bucket::CHUNK_SIZE,
+            concurrency: 4,
+        }
+    }
+}
+
+impl UploadConfig {
+    /// Check the config against the limits of S3 for an upload of `size` bytes.
+    pub fn validate(&self, size: u64) -> Result<()> {
+        if self.part_size < MIN_PART_SIZE {
+            return Err(anyhow!(
+                "Part size must be at least {} bytes, got {}",
+                MIN_PART_SIZE,
+                self.part_size
+            ));
+        }
+        if self.concurrency == 0 {
+            return Err(anyhow!("Upload concurrency must be at least 1"));
+        }
+        if size >= self.threshold as u64 {
+            let parts = size.div_ceil(self.part_size as u64);
+            if parts > MAX_PARTS {
+                return Err(anyhow!(
+                    "Uploading {} bytes in {} byte parts takes {} parts, at most {} are allowed",
+                    size,
+                    self.part_size,
+                    parts,
+                    MAX_PARTS
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// [AWS Documentation](https://docs.aws.amazon.com/AmazonS3/latest/API/API_CopyObject.html)
+///
+/// Whether a copy keeps the user metadata of the source object, or replaces it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MetadataDirective {
+    #[default]
+    Copy,
+    Replace,
+}
+
+impl fmt::Display for MetadataDirective {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetadataDirective::Copy => write!(f, "COPY"),
+            MetadataDirective::Replace => write!(f, "REPLACE"),
+        }
+    }
+}
+
+/// Optional settings for a server side copy, sent as (signed) request headers.
+///
+/// # Example
+///
+/// ```
+/// use s3::object_ops::{CopyObjectOptions, MetadataDirective};
+///
+/// // Drop the metadata of the source object in favour of new metadata
+/// let options = CopyObjectOptions::new()
+///     .with_metadata_directive(MetadataDirective::Replace)
+///     .with_metadata(&[("owner", "data-team")]);
+///
+/// // Only copy the version of the source that was read before, S3 answers with
+/// // `412 Precondition Failed` otherwise
+/// let options = CopyObjectOptions::new().with_copy_source_if_match("\"etag\"");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CopyObjectOptions {
+    metadata_directive: MetadataDirective,
+    metadata: Vec<(String, String)>,
+    copy_source_if_match: Option<String>,
+    copy_source_if_none_match: Option<String>,
+    copy_source_if_modified_since: Option<DateTime<Utc>>,
+    copy_source_if_unmodified_since: Option<DateTime<Utc>>,
+}
+
+impl CopyObjectOptions {
+    pub fn new() -> Self {
+        CopyObjectOptions::default()
+    }
+
+    pub fn with_metadata_directive(mut self, metadata_directive: MetadataDirective) -> Self {
+        self.metadata_directive = metadata_directive;
+        self
+    }
+
+    pub fn metadata_directive(&self) -> MetadataDirective {
+        self.metadata_directive
+    }
+
+    /// Metadata of the copy, only valid with `MetadataDirective::Replace`, S3 ignores it when
+    /// copying the metadata of the source object.
+    pub fn with_metadata<S: AsRef<str>>(mut self, metadata: &[(S, S)]) -> Self {
+        self.metadata = metadata
+            .iter()
+            .map(|(key, value)| (key.as_ref().to_string(), value.as_ref().to_string()))
+            .collect();
+        self
+    }
+
+    pub fn metadata(&self) -> &[(String, String)] {
+        &self.metadata
+    }
+
+    /// [AWS Documentation](https://docs.aws.amazon.com/AmazonS3/latest/API/API_CopyObject.html#API_CopyObject_RequestSyntax)
+    ///
+    /// Only copy if the ETag of the source is `e_tag`, including its quotes.
+    pub fn with_copy_source_if_match(mut self, e_tag: &str) -> Self {
+        self.copy_source_if_match = Some(e_tag.to_string());
+        self
+    }
+
+    pub fn copy_source_if_match(&self) -> Option<&str> {
+        self.copy_source_if_match.as_deref()
+    }
+
+    /// Only copy if the ETag of the source is not `e_tag`, including its quotes.
+    pub fn with_copy_source_if_none_match(mut self, e_tag: &str) -> Self {
+        self.copy_source_if_none_match = Some(e_tag.to_string());
+        self
+    }
+
+    pub fn copy_source_if_none_match(&self) -> Option<&str> {
+        self.copy_source_if_none_match.as_deref()
+    }
+
+    /// Only copy if the source was modified after `since`.
+    pub fn with_copy_source_if_modified_since(mut self, since: DateTime<Utc>) -> Self {
+        self.copy_source_if_modified_since = Some(since);
+        self
+    }
+
+    pub fn copy_source_if_modified_since(&self) -> Option<DateTime<Utc>> {
+        self.copy_source_if_modified_since
+    }
+
+    /// Only copy if the source was not modified after `since`.
+    pub fn with_copy_source_if_unmodified_since(mut self, since: DateTime<Utc>) -> Self {
+        self.copy_source_if_unmodified_since = Some(since);
+        self
+    }
+
+    pub fn copy_source_if_unmodified_since(&self) -> Option<DateTime<Utc>> {
+        self.copy_source_if_unmodified_since
+    }
+
+    pub fn add_headers(&self, headers: &mut HeaderMap) -> Result<()> {
+        if self.metadata_directive == MetadataDirective::Copy && !self.metadata.is_empty() {
+            return Err(anyhow!(
+                "Metadata is only applied to copies with MetadataDirective::Replace"
+            ));
+        }
+        headers.insert(
+            HeaderName::from_static("x-amz-metadata-directive"),
+            self.metadata_directive.to_string().parse()?,
+        );
+        if let Some(ref e_tag) = self.copy_source_if_match {
+            headers.insert(
+                HeaderName::from_static("x-amz-copy-source-if-match"),
+                e_tag.parse()?,
+            );
+        }
+        if let Some(ref e_tag) = self.copy_source_if_none_match {
+            headers.insert(
+                HeaderName::from_static("x-amz-copy-source-if-none-match"),
+                e_tag.parse()?,
+            );
+        }
+        for (name, since) in &[
+            (
+                "x-amz-copy-source-if-modified-since",
+                self.copy_source_if_modified_since,
+            ),
+            (
+                "x-amz-copy-source-if-unmodified-since",
+                self.copy_source_if_unmodified_since,
+            ),
+        ] {
+            if let Some(since) = since {
+                headers.insert(
+                    HeaderName::from_static(name),
+                    since.format(HTTP_DATE).to_string().parse()?,
+                );
+            }
+        }
+        add_metadata_headers(&self.metadata, headers)
+    }
+}
+
+/// [AWS Documentation](https://docs.aws.amazon.com/AmazonS3/latest/API/API_GetObject.html#API_GetObject_RequestSyntax)
+///
+/// Headers S3 answers a GET with instead of the ones stored with the object, sent as (signed)
+/// `response-*` query parameters, see `Bucket::presign_get_with_response_overrides`.
+///
+/// # Example
+///
+/// ```
+/// use s3::object_ops::ResponseOverrides;
+///
+/// // Have browsers save the download under a given name
+/// let overrides = ResponseOverrides::new()
+///     .with_content_disposition("attachment; filename=\"report.pdf\"")
+///     .with_cache_control("no-store");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ResponseOverrides {
+    content_type: Option<String>,
+    content_language: Option<String>,
+    expires: Option<DateTime<Utc>>,
+    cache_control: Option<String>,
+    content_disposition: Option<String>,
+    content_encoding: Option<String>,
+}
+
+impl ResponseOverrides {
+    pub fn new() -> Self {
+        ResponseOverrides::default()
+    }
+
+    pub fn with_content_type(mut self, content_type: &str) -> Self {
+        self.content_type = Some(content_type.to_string());
+        self
+    }
+
+    pub fn with_content_language(mut self, content_language: &str) -> Self {
+        self.content_language = Some(content_language.to_string());
+        self
+    }
+
+    pub fn with_expires(mut self, expires: DateTime<Utc>) -> Self {
+        self.expires = Some(expires);
+        self
+    }
+
+    pub fn with_cache_control(mut self, cache_control: &str) -> Self {
+        self.cache_control = Some(cache_control.to_string());
+        self
+    }
+
+    pub fn with_content_disposition(mut self, content_disposition: &str) -> Self {
+        self.content_disposition = Some(content_disposition.to_string());
+        self
+    }
+
+    pub fn with_content_encoding(mut self, content_encoding: &str) -> Self {
+        self.content_encoding = Some(content_encoding.to_string());
+        self
+    }
+
+    /// The `response-*` query parameters, unencoded.
+    pub fn query_pairs(&self) -> Vec<(&'static str, String)> {
+        let expires = self
+            .expires
+            .map(|expires| expires.format(HTTP_DATE).to_string());
+        vec![
+            ("response-content-type", self.content_type.clone()),
+            ("response-content-language", self.content_language.clone()),
+            ("response-expires", expires),
+            ("response-cache-control", self.cache_control.clone()),
+            (
+                "response-content-disposition",
+                self.content_disposition.clone(),
+            ),
+            ("response-content-encoding", self.content_encoding.clone()),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|value| (name, value)))
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        validate_part, validate_tags, AwsChunkedEncoder, ChecksumAlgorithm, CopyObjectOptions,
+        Grantee, MetadataDirective, Permission, PutObjectOptions, ResponseOverrides, Sse,
+        UploadConfig, AWS_CHUNK_SIZE, MAX_TAGS, MIN_PART_SIZE,
+    };
+    use crate::error::S3Error;
+    use crate::serde_types::{HeadObjectResult, StorageClass};
+    use chrono::{TimeZone, Utc};
+    use http::HeaderMap;
+
+    #[test]
+    fn test_put_options_unchanged() {
+        let content = b"content addressed";
+        let head = HeadObjectResult {
+            etag: Some(format!("\"{:x}\"", md5::compute(content))),
+            content_type: Some("text/html".to_string()),
+            cache_control: Some("max-age=60".to_string()),
+            website_redirect_location: Some("/new.html".to_string()),
+            ..Default::default()
+        };
+        let options = PutObjectOptions::new()
+            .with_cache_control("max-age=60")
+            .with_website_redirect_location("/new.html");
+        assert!(options.is_unchanged(&head, content, "text/html"));
+        assert!(!options.is_unchanged(&head, b"other", "text/html"));
+        assert!(!options.is_unchanged(&head, content, "text/plain"));
+        assert!(!PutObjectOptions::new().is_unchanged(&head, content, "text/html"));
+        assert!(!options
+            .clone()
+            .with_website_redirect_location("/other.html")
+            .is_unchanged(&head, content, "text/html"));
+        assert!(!options
+            .clone()
+            .with_storage_class(StorageClass::StandardIa)
+            .is_unchanged(&head, content, "text/html"));
+        assert!(options
+            .with_storage_class(StorageClass::Standard)
+            .is_unchanged(&head, content, "text/html"));
+    }
+
+    #[test]
+    fn test_website_redirect_location_header() {
+        let mut headers = HeaderMap::new();
+        PutObjectOptions::new()
+            .with_website_redirect_location("/index.html")
+            .add_headers(&mut headers)
+            .unwrap();
+        assert_eq!(
+            headers.get("x-amz-website-redirect-location").unwrap(),
+            "/index.html"
+        );
+
+        let mut headers = HeaderMap::new();
+        PutObjectOptions::new().add_headers(&mut headers).unwrap();
+        assert!(headers.is_empty());
+    }
+
+    #[test]
+    fn test_tagging_header() {
+        let mut headers = HeaderMap::new();
+        PutObjectOptions::new()
+            .with_tags(&[("Project", "rust s3"), ("Team", "a+b")])
+            .add_headers(&mut headers)
+            .unwrap();
+        assert_eq!(
+            headers.get("x-amz-tagging").unwrap(),
+            "Project=rust+s3&Team=a%2Bb"
+        );
+
+        let mut headers = HeaderMap::new();
+        assert!(PutObjectOptions::new()
+            .with_tags(&[("aws:reserved", "value")])
+            .add_headers(&mut headers)
+            .is_err());
+    }
+
+    #[test]
+    fn test_sse_headers() {
+        let headers = |sse: Sse| {
+            let mut headers = HeaderMap::new();
+            PutObjectOptions::new()
+                .with_sse(sse)
+                .add_headers(&mut headers)
+                .unwrap();
+            headers
+        };
+
+        let aes256 = headers(Sse::Aes256);
+        assert_eq!(aes256.len(), 1);
+        assert_eq!(
+            aes256.get("x-amz-server-side-encryption").unwrap(),
+            "AES256"
+        );
+
+        let kms = headers(Sse::Kms("alias/rust-s3".to_string()));
+        assert_eq!(kms.get("x-amz-server-side-encryption").unwrap(), "aws:kms");
+        assert_eq!(
+            kms.get("x-amz-server-side-encryption-aws-kms-key-id")
+                .unwrap(),
+            "alias/rust-s3"
+        );
+
+        let customer = headers(Sse::customer_key(&[0; 32]));
+        assert!(customer.get("x-amz-server-side-encryption").is_none());
+        assert_eq!(
+            customer
+                .get("x-amz-server-side-encryption-customer-algorithm")
+                .unwrap(),
+            "AES256"
+        );
+        assert_eq!(
+            customer
+                .get("x-amz-server-side-encryption-customer-key")
+                .unwrap(),
+            "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA="
+        );
+        assert_eq!(
+            customer
+                .get("x-amz-server-side-encryption-customer-key-md5")
+                .unwrap(),
+            "cLyPS3KoaSFGi/joRB3OUQ=="
+        );
+    }
+
+    #[test]
+    fn test_validate_tags() {
+        assert!(validate_tags(&[("Key", "Value"), ("Other", "")]).is_ok());
+        assert!(validate_tags::<&str>(&[]).is_ok());
+
+        let too_many: Vec<(String, String)> = (0..=MAX_TAGS)
+            .map(|i| (format!("Key{}", i), "Value".to_string()))
+            .collect();
+        assert!(validate_tags(&too_many).is_err());
+
+        assert!(validate_tags(&[("", "Value")]).is_err());
+        assert!(validate_tags(&[("Key".repeat(43).as_str(), "Value")]).is_err());
+        assert!(validate_tags(&[("Key", "V".repeat(257).as_str())]).is_err());
+        assert!(validate_tags(&[("aws:Key", "Value")]).is_err());
+        assert!(validate_tags(&[("Key", "Value?")]).is_err());
+        assert!(validate_tags(&[("Key", "Value"), ("Key", "Other")]).is_err());
+    }
+
+    #[test]
+    fn test_website_redirect_location_invalid() {
+        let mut headers = HeaderMap::new();
+        assert!(PutObjectOptions::new()
+            .with_website_redirect_location("index.html")
+            .add_headers(&mut headers)
+            .is_err());
+    }
+
+    #[test]
+    fn test_metadata_headers() {
+        let mut headers = HeaderMap::new();
+        PutObjectOptions::new()
+            .with_metadata(&[("Owner", "data-team")])
+            .add_headers(&mut headers)
+            .unwrap();
+        assert_eq!(headers.get("x-amz-meta-owner").unwrap(), "data-team");
+
+        let mut headers = HeaderMap::new();
+        assert!(PutObjectOptions::new()
+            .with_metadata(&[("not a header", "value")])
+            .add_headers(&mut headers)
+            .is_err());
+    }
+
+    #[test]
+    fn test_storage_class_header() {
+        let mut headers = HeaderMap::new();
+        PutObjectOptions::new()
+            .with_storage_class(StorageClass::DeepArchive)
+            .add_headers(&mut headers)
+            .unwrap();
+        assert_eq!(headers.get("x-amz-storage-class").unwrap(), "DEEP_ARCHIVE");
+    }
+
+    #[test]
+    fn test_grant_headers() {
+        let mut headers = HeaderMap::new();
+        PutObjectOptions::new()
+            .with_grant(
+                Permission::Read,
+                Grantee::CanonicalUser {
+                    id: "1234".to_string(),
+                    display_name: None,
+                },
+            )
+            .with_grant(Permission::Read, Grantee::group(Grantee::ALL_USERS))
+            .with_grant(
+                Permission::FullControl,
+                Grantee::Email("ops@example.com".to_string()),
+            )
+            .add_headers(&mut headers)
+            .unwrap();
+        assert_eq!(
+            headers.get("x-amz-grant-read").unwrap(),
+            "id=\"1234\", uri=\"http://acs.amazonaws.com/groups/global/AllUsers\""
+        );
+        assert_eq!(
+            headers.get("x-amz-grant-full-control").unwrap(),
+            "emailAddress=\"ops@example.com\""
+        );
+        assert!(headers.get("x-amz-grant-read-acp").is_none());
+
+        let mut headers = HeaderMap::new();
+        assert!(PutObjectOptions::new()
+            .with_grant(Permission::Write, Grantee::group(Grantee::ALL_USERS))
+            .add_headers(&mut headers)
+            .is_err());
+        assert!(headers.get("x-amz-grant-write").is_none());
+    }
+
+    #[test]
+    fn test_copy_source_condition_headers() {
+        let mut headers = HeaderMap::new();
+        CopyObjectOptions::new()
+            .with_copy_source_if_match("\"etag\"")
+            .with_copy_source_if_unmodified_since(Utc.ymd(2015, 10, 21).and_hms(7, 28, 0))
+            .add_headers(&mut headers)
+            .unwrap();
+        assert_eq!(
+            headers.get("x-amz-copy-source-if-match").unwrap(),
+            "\"etag\""
+        );
+        assert_eq!(
+            headers
+                .get("x-amz-copy-source-if-unmodified-since")
+                .unwrap(),
+            "Wed, 21 Oct 2015 07:28:00 GMT"
+        );
+        assert!(headers.get("x-amz-copy-source-if-none-match").is_none());
+        assert!(headers.get("x-amz-copy-source-if-modified-since").is_none());
+    }
+
+    #[test]
+    fn test_response_overrides_query_pairs() {
+        let overrides = ResponseOverrides::new()
+            .with_content_type("text/csv")
+            .with_expires(Utc.ymd(2015, 10, 21).and_hms(7, 28, 0));
+        assert_eq!(
+            overrides.query_pairs(),
+            vec![
+                ("response-content-type", "text/csv".to_string()),
+                (
+                    "response-expires",
+                    "Wed, 21 Oct 2015 07:28:00 GMT".to_string()
+                ),
+            ]
+        );
+        assert!(ResponseOverrides::new().query_pairs().is_empty());
+    }
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(ChecksumAlgorithm::Crc32.checksum(b"123456789"), "y/Q5Jg==");
+        assert_eq!(ChecksumAlgorithm::Crc32c.checksum(b"123456789"), "4waSgw==");
+        assert_eq!(
+            ChecksumAlgorithm::Sha256.checksum(b"123456789"),
+            "FeKw08M4keuw8e9gnsQZQgwg4yDOlMZfvIwzEkSOsiU="
+        );
+        assert_eq!(ChecksumAlgorithm::Crc32c.to_string(), "CRC32C");
+    }
+
+    #[test]
+    fn test_aws_chunked_body() {
+        let mut encoder = AwsChunkedEncoder::new(9, ChecksumAlgorithm::Crc32c);
+        let mut body = encoder.encode(b"1234").unwrap();
+        assert!(body.is_empty());
+        body.extend(encoder.encode(b"56789").unwrap());
+        assert!(!encoder.is_finished());
+        body.extend(encoder.encode(b"").unwrap());
+        assert!(encoder.is_finished());
+        assert_eq!(
+            body,
+            b"9\r\n123456789\r\n0\r\nx-amz-checksum-crc32c:4waSgw==\r\n\r\n".to_vec()
+        );
+        assert_eq!(
+            AwsChunkedEncoder::encoded_length(9, ChecksumAlgorithm::Crc32c),
+            body.len() as u64
+        );
+
+        // Every chunk but the last is full, however the content is read
+        let content = vec![b'a'; 2 * AWS_CHUNK_SIZE + 1];
+        let mut encoder = AwsChunkedEncoder::new(content.len() as u64, ChecksumAlgorithm::Sha256);
+        let mut body = Vec::new();
+        for piece in content.chunks(1000) {
+            body.extend(encoder.encode(piece).unwrap());
+        }
+        body.extend(encoder.encode(b"").unwrap());
+        let full = "a".repeat(AWS_CHUNK_SIZE);
+        let expected = format!(
+            "10000\r\n{}\r\n10000\r\n{}\r\n1\r\na\r\n0\r\nx-amz-checksum-sha256:{}\r\n\r\n",
+            full,
+            full,
+            ChecksumAlgorithm::Sha256.checksum(&content)
+        );
+        assert_eq!(String::from_utf8(body).unwrap(), expected);
+        assert_eq!(
+            AwsChunkedEncoder::encoded_length(content.len() as u64, ChecksumAlgorithm::Sha256),
+            expected.len() as u64
+        );
+
+        let mut encoder = AwsChunkedEncoder::new(0, ChecksumAlgorithm::Crc32);
+        let body = encoder.encode(b"").unwrap();
+        assert_eq!(body, b"0\r\nx-amz-checksum-crc32:AAAAAA==\r\n\r\n".to_vec());
+        assert_eq!(
+            AwsChunkedEncoder::encoded_length(0, ChecksumAlgorithm::Crc32),
+            body.len() as u64
+        );
+
+        // Content that doesn't match the announced length
+        let mut encoder = AwsChunkedEncoder::new(9, ChecksumAlgorithm::Crc32c);
+        encoder.encode(b"1234").unwrap();
+        assert!(encoder.encode(b"").is_err());
+        let mut encoder = AwsChunkedEncoder::new(3, ChecksumAlgorithm::Crc32c);
+        assert!(encoder.encode(b"1234").is_err());
+    }
+
+    #[test]
+    fn test_standard_headers() {
+        let mut headers = HeaderMap::new();
+        PutObjectOptions::new()
+            .with_cache_control("max-age=3600")
+            .with_content_disposition("attachment; filename=\"report.pdf\"")
+            .with_content_encoding("gzip")
+            .with_content_language("de-CH")
+            .with_expires(Utc.ymd(2015, 10, 21).and_hms(7, 28, 0))
+            .add_headers(&mut headers)
+            .unwrap();
+        assert_eq!(headers.get("cache-control").unwrap(), "max-age=3600");
+        assert_eq!(
+            headers.get("content-disposition").unwrap(),
+            "attachment; filename=\"report.pdf\""
+        );
+        assert_eq!(headers.get("content-encoding").unwrap(), "gzip");
+        assert_eq!(headers.get("content-language").unwrap(), "de-CH");
+        assert_eq!(
+            headers.get("expires").unwrap(),
+            "Wed, 21 Oct 2015 07:28:00 GMT"
+        );
+    }
+
+    #[test]
+    fn test_copy_metadata_directive() {
+        let mut headers = HeaderMap::new();
+        CopyObjectOptions::new().add_headers(&mut headers).unwrap();
+        assert_eq!(headers.get("x-amz-metadata-directive").unwrap(), "COPY");
+        assert_eq!(headers.len(), 1);
+
+        let mut headers = HeaderMap::new();
+        CopyObjectOptions::new()
+            .with_metadata_directive(MetadataDirective::Replace)
+            .with_metadata(&[("owner", "data-team")])
+            .add_headers(&mut headers)
+            .unwrap();
+        assert_eq!(headers.get("x-amz-metadata-directive").unwrap(), "REPLACE");
+        assert_eq!(headers.get("x-amz-meta-owner").unwrap(), "data-team");
+
+        let mut headers = HeaderMap::new();
+        assert!(CopyObjectOptions::new()
+            .with_metadata(&[("owner", "data-team")])
+            .add_headers(&mut headers)
+            .is_err());
+    }
+
+    #[test]
+    fn test_upload_config_validate() {
+        let config = UploadConfig::default();
+        assert!(config.validate(0).is_ok());
+        assert!(config.validate(1 << 30).is_ok());
+
+        let config = UploadConfig {
+            part_size: MIN_PART_SIZE - 1,
+            ..Default::default()
+        };
+        assert!(config.validate(0).is_err());
+
+        let config = UploadConfig {
+            concurrency: 0,
+            ..Default::default()
+        };
+        assert!(config.validate(0).is_err());
+
+        // 10001 parts
+        let config = UploadConfig {
+            threshold: MIN_PART_SIZE,
+            part_size: MIN_PART_SIZE,
+            concurrency: 1,
+        };
+        assert!(config.validate(MIN_PART_SIZE as u64 * 10_000).is_ok());
+        assert!(config.validate(MIN_PART_SIZE as u64 * 10_000 + 1).is_err());
+    }
+
+    #[test]
+    fn test_validate_part() {
+        assert!(validate_part(1, MIN_PART_SIZE, false).is_ok());
+        assert!(validate_part(10_000, 1, true).is_ok());
+        assert!(validate_part(1, MIN_PART_SIZE - 1, false).is_err());
+
+        for part_number in &[0, 10_001] {
+            let error = validate_part(*part_number, MIN_PART_SIZE, false).unwrap_err();
+            assert_eq!(
+                error.downcast_ref::<S3Error>(),
+                Some(&S3Error::InvalidPartNumber {
+                    part_number: *part_number
+                })
+            );
+        }
+    }
+}
@@ -1,21 +1,189 @@
 use chrono::{DateTime, Utc};
-use hmac::Mac;
-use hmac::NewMac;
 use maybe_async::maybe_async;
 use std::io::Write;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use url::Url;
 
 use crate::bucket::Bucket;
-use crate::command::Command;
-use crate::signing;
-use crate::LONG_DATE;
-use anyhow::anyhow;
+use crate::command::{Command, HttpMethod};
+use crate::creds::Credentials;
+use crate::error::S3Error;
+use crate::signing::{self, SignatureVersion};
+use crate::{HTTP_DATE, LONG_DATE};
 use anyhow::Result;
 use http::header::{
-    HeaderName, ACCEPT, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, DATE, HOST, RANGE,
+    HeaderName, ACCEPT, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, DATE, HOST, IF_MATCH, RANGE,
 };
 use http::HeaderMap;
 
+/// Append the query parameter `name`, with `value` URI encoded, to `url`.
+fn push_query_param(url: &mut String, name: &str, value: &str) {
+    let separator = if url.contains('?') { '&' } else { '?' };
+    url.push_str(&format!(
+        "{}{}={}",
+        separator,
+        name,
+        signing::uri_encode(value, true)
+    ));
+}
+
+/// Max number of `307 Temporary Redirect` responses followed for a single request.
+pub const MAX_TEMPORARY_REDIRECTS: usize = 3;
+
+/// Delay before the first retry of a request, doubled for each further one, see
+/// `Bucket::with_retries`.
+pub const RETRY_BASE_DELAY: Duration = Duration::from_millis(100);
+
+/// Retries of a request failing with `404 Not Found` or a connection error, like an unresolvable
+/// host, because its bucket was only just created by `Bucket::create`, and is not known to all
+/// of S3 or the DNS yet. Made in addition to the retries of `Bucket::with_retries`.
+pub const POST_CREATE_RETRIES: u32 = 4;
+
+/// Delay before each of the `POST_CREATE_RETRIES`.
+pub const POST_CREATE_DELAY: Duration = Duration::from_millis(500);
+
+/// How long after its creation requests to a bucket get the `POST_CREATE_RETRIES`.
+pub const POST_CREATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Retries left for a single request, with exponential backoff and jitter.
+pub struct Retries {
+    max: u32,
+    attempts: u32,
+    backoff: Duration,
+    max_backoff: Duration,
+    post_create: u32,
+    bucket: Bucket,
+}
+
+impl Retries {
+    pub fn new(bucket: &Bucket, command: &Command) -> Self {
+        let max = match command {
+            // A retried initiation could leave an upload behind that nobody completes
+            Command::InitiateMultipartUpload { .. } => 0,
+            _ => bucket.max_retries(),
+        };
+        Retries {
+            max,
+            attempts: 0,
+            backoff: Duration::from_secs(0),
+            max_backoff: bucket.max_backoff(),
+            post_create: if bucket.created_within(POST_CREATE_WINDOW) {
+                POST_CREATE_RETRIES
+            } else {
+                0
+            },
+            bucket: bucket.clone(),
+        }
+    }
+
+    /// Delay to wait before retrying a request that may have failed because its bucket was
+    /// created moments ago, see `POST_CREATE_RETRIES`, `None` if it was not, or when out of
+    /// these retries.
+    pub fn next_post_create_delay(&mut self) -> Option<Duration> {
+        if self.post_create == 0 {
+            return None;
+        }
+        self.post_create -= 1;
+        self.bucket.count_retry();
+        Some(POST_CREATE_DELAY)
+    }
+
+    /// Delay to wait before retrying, `None` when out of retries, or when waiting would take the
+    /// total backoff of the request over `Bucket::max_backoff`.
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        if self.attempts >= self.max {
+            return None;
+        }
+        let ceiling = RETRY_BASE_DELAY * 2u32.pow(self.attempts.min(16));
+        // Half of the delay is random, so that clients failing together do not retry together
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.subsec_nanos() as u64)
+            .unwrap_or(0);
+        let jitter = Duration::from_nanos(nanos % (ceiling.as_nanos() as u64 / 2 + 1));
+        let delay = ceiling / 2 + jitter;
+        if self.backoff + delay > self.max_backoff {
+            return None;
+        }
+        self.attempts += 1;
+        self.backoff += delay;
+        self.bucket.count_retry();
+        Some(delay)
+    }
+}
+
+/// Whether `error`, from sending a request, is a `5xx` response turned into an error by the
+/// `fail-on-err` feature.
+pub fn is_server_error(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<S3Error>().and_then(S3Error::status),
+        Some(status) if status >= 500
+    )
+}
+
+/// Whether `error`, from sending a request, is a `404` response turned into an error by the
+/// `fail-on-err` feature.
+pub fn is_not_found(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<S3Error>().and_then(S3Error::status) == Some(404)
+}
+
+/// Bucket to retry `command` against after a response with `status` and `location`, or `None`
+/// if the response should be returned as is.
+///
+/// S3 answers with `307 Temporary Redirect` to a regional endpoint for a short while after a
+/// bucket is created, until its DNS entry propagates. Since the signature covers the host, the
+/// request has to be signed again for the new endpoint, so redirects are only followed for
+/// idempotent requests, and never automatically by the HTTP client.
+pub fn temporary_redirect(
+    bucket: &Bucket,
+    command: &Command,
+    status: u16,
+    location: Option<&str>,
+) -> Result<Option<Bucket>> {
+    if status != 307 || !bucket.follows_redirects() {
+        return Ok(None);
+    }
+    if let HttpMethod::Post = command.http_verb() {
+        return Ok(None);
+    }
+    match location {
+        Some(location) => Ok(Some(bucket.redirected(location)?)),
+        None => Ok(None),
+    }
+}
+
+/// Bucket to retry against after a response with `status` and the `x-amz-bucket-region`
+/// header, `None` if the response should be returned as is, see `Bucket::with_region_redirect`.
+///
+/// S3 names the bucket's region when a request went to, or was signed for, another one. The
+/// retry is signed again, with the new region in the credential scope.
+pub fn region_redirect(
+    bucket: &Bucket,
+    status: u16,
+    bucket_region: Option<&str>,
+) -> Result<Option<Bucket>> {
+    match bucket_region {
+        Some(region) if is_region_redirect(bucket, status, bucket_region) => {
+            Ok(Some(bucket.region_redirected(region)?))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Whether `region_redirect` retries the response against another region. Such responses are
+/// not turned into errors by the `fail-on-err` feature, so that `response` gets to see them.
+pub fn is_region_redirect(bucket: &Bucket, status: u16, bucket_region: Option<&str>) -> bool {
+    bucket.follows_region_redirects()
+        && matches!(status, 301 | 307 | 400)
+        && bucket_region.is_some_and(|region| region != bucket.region().to_string())
+}
+
+/// Pieces of a request body as they are produced, see `Request::response_data_streamed`.
+#[cfg(not(feature = "sync"))]
+pub type BodyStream<'a> = futures::channel::mpsc::Receiver<std::io::Result<Vec<u8>>>;
+#[cfg(feature = "sync")]
+pub type BodyStream<'a> = Box<dyn Iterator<Item = std::io::Result<Vec<u8>>> + 'a>;
+
 #[maybe_async]
 pub trait Request {
     type Response;
@@ -24,17 +192,47 @@ pub trait Request {
     async fn response(&self) -> Result<Self::Response>;
     async fn response_data(&self, etag: bool) -> Result<(Vec<u8>, u16)>;
     async fn response_data_to_writer<T: Write + Send>(&self, writer: &mut T) -> Result<u16>;
+    /// Like `response_data_to_writer`, but only streams the body into `writer` if the response
+    /// has the status `expected`, so that error documents never end up in it. Returns the status
+    /// code and the number of bytes written.
+    async fn response_data_to_writer_if_status<T: Write + Send>(
+        &self,
+        writer: &mut T,
+        expected: u16,
+    ) -> Result<(u16, u64)>;
+    /// Like `response_data` with the ETag, but sends the pieces of `body` as they are produced,
+    /// instead of `request_body`, for `Command::content_length` bytes. A streamed body can't be
+    /// sent again, so the request is attempted once, without retries or redirects.
+    async fn response_data_streamed(&self, body: BodyStream<'_>) -> Result<(Vec<u8>, u16)>;
     async fn response_header(&self) -> Result<(Self::HeaderMap, u16)>;
+    async fn response_data_with_headers(&self) -> Result<(Vec<u8>, Self::HeaderMap, u16)>;
+    /// Like `response_data`, but fails with `S3Error::BodyTooLarge` as soon as either the
+    /// `Content-Length` header or the bytes read so far exceed `max_body_size`.
+    async fn response_data_with_max_size(&self, max_body_size: u64) -> Result<(Vec<u8>, u16)>;
     fn datetime(&self) -> DateTime<Utc>;
     fn bucket(&self) -> Bucket;
+    /// Credentials of the bucket when the request was created, so that all signing steps use
+    /// the same ones, even if the bucket's credentials are replaced concurrently.
+    fn credentials(&self) -> Credentials;
     fn command(&self) -> Command;
     fn path(&self) -> String;
 
+    fn access_key(&self) -> Option<String> {
+        self.credentials()
+            .access_key
+            .map(|key| key.replace('\n', ""))
+    }
+
+    fn secret_key(&self) -> Option<String> {
+        self.credentials()
+            .secret_key
+            .map(|key| key.replace('\n', ""))
+    }
+
     fn signing_key(&self) -> Result<Vec<u8>> {
         signing::signing_key(
             &self.datetime(),
             &self
-                .bucket()
                 .secret_key()
                 .expect("Secret key must be provided to sign headers, found None"),
             &self.bucket().region(),
@@ -53,6 +251,14 @@ pub trait Request {
             let body = data.to_string();
             // assert_eq!(body, "body".to_string());
             body.as_bytes().to_vec()
+        } else if let Command::DeleteObjects { data } = &self.command() {
+            data.to_string().as_bytes().to_vec()
+        } else if let Command::SelectObjectContent { data } = &self.command() {
+            data.to_string().as_bytes().to_vec()
+        } else if let Command::PutBucketInventoryConfiguration { configuration } = self.command() {
+            configuration.to_string().as_bytes().to_vec()
+        } else if let Command::PutObjectLockConfiguration { configuration } = self.command() {
+            configuration.to_string().as_bytes().to_vec()
         } else if let Command::CreateBucket { config } = &self.command() {
             if let Some(payload) = config.location_constraint_payload() {
                 Vec::from(payload)
@@ -73,15 +279,18 @@ pub trait Request {
     }
 
     fn host_header(&self) -> String {
-        self.bucket().host()
+        self.bucket().signing_host()
     }
 
     fn presigned(&self) -> Result<String> {
         let expiry = match self.command() {
-            Command::PresignGet { expiry_secs } => expiry_secs,
+            Command::PresignGet { expiry_secs, .. } => expiry_secs,
             Command::PresignPut { expiry_secs, .. } => expiry_secs,
             _ => unreachable!(),
         };
+        if self.bucket().signature_version() == SignatureVersion::V2 {
+            return self.presigned_v2(expiry);
+        }
 
         #[allow(clippy::collapsible_match)]
         if let Command::PresignPut { custom_headers, .. } = self.command() {
@@ -102,6 +311,64 @@ pub trait Request {
         ))
     }
 
+    /// Presigned url with V2 query string authentication, signing the custom headers of a
+    /// `PresignPut` and the response overrides of a `PresignGet`.
+    fn presigned_v2(&self, expiry: u32) -> Result<String> {
+        let bucket = self.bucket();
+        let credentials = self.credentials();
+        let token = credentials
+            .security_token
+            .as_ref()
+            .or(credentials.session_token.as_ref());
+        let expires = self.datetime().timestamp() + i64::from(expiry);
+
+        let mut headers = HeaderMap::new();
+        if let Command::PresignPut {
+            custom_headers: Some(custom_headers),
+            ..
+        } = self.command()
+        {
+            for (k, v) in custom_headers.iter() {
+                headers.insert(k.clone(), v.clone());
+            }
+        }
+        if let Some(token) = token {
+            headers.insert(
+                HeaderName::from_static("x-amz-security-token"),
+                token.parse()?,
+            );
+        }
+
+        let mut url = self.url().to_string();
+        if let Command::PresignGet {
+            response_overrides: Some(response_overrides),
+            ..
+        } = self.command()
+        {
+            for (name, value) in response_overrides.query_pairs() {
+                push_query_param(&mut url, name, &value);
+            }
+        }
+
+        let name = bucket.name();
+        let string_to_sign = signing::string_to_sign_v2_query(
+            &self.command().http_verb().to_string(),
+            &Url::parse(&url)?,
+            &headers,
+            expires,
+            bucket.is_subdomain_style().then_some(name.as_str()),
+        )?;
+        let signature = signing::signature_v2(&self.secret_key().unwrap(), &string_to_sign)?;
+
+        push_query_param(&mut url, "AWSAccessKeyId", &self.access_key().unwrap());
+        push_query_param(&mut url, "Expires", &expires.to_string());
+        push_query_param(&mut url, "Signature", &signature);
+        if let Some(token) = token {
+            push_query_param(&mut url, "x-amz-security-token", token);
+        }
+        Ok(url)
+    }
+
     fn presigned_authorization(&self, custom_headers: Option<&HeaderMap>) -> Result<String> {
         let mut headers = HeaderMap::new();
         let host_header = self.host_header();
@@ -113,17 +380,13 @@ pub trait Request {
         }
         let canonical_request = self.presigned_canonical_request(&headers)?;
         let string_to_sign = self.string_to_sign(&canonical_request);
-        let mut hmac =
-            signing::HmacSha256::new_varkey(&self.signing_key()?).map_err(|e| anyhow! {"{}",e})?;
-        hmac.update(string_to_sign.as_bytes());
-        let signature = hex::encode(hmac.finalize().into_bytes());
         // let signed_header = signing::signed_header_string(&headers);
-        Ok(signature)
+        signing::signature(&self.signing_key()?, &string_to_sign)
     }
 
     fn presigned_canonical_request(&self, headers: &HeaderMap) -> Result<String> {
         let expiry = match self.command() {
-            Command::PresignGet { expiry_secs } => expiry_secs,
+            Command::PresignGet { expiry_secs, .. } => expiry_secs,
             Command::PresignPut { expiry_secs, .. } => expiry_secs,
             _ => unreachable!(),
         };
@@ -149,28 +412,39 @@ pub trait Request {
     }
 
     fn presigned_url_no_sig(&self, expiry: u32, custom_headers: Option<&HeaderMap>) -> Result<Url> {
-        let bucket = self.bucket();
-        let token = if let Some(security_token) = bucket.security_token() {
-            Some(security_token)
-        } else if let Some(session_token) = bucket.session_token() {
-            Some(session_token)
+        let credentials = self.credentials();
+        let token = if let Some(ref security_token) = credentials.security_token {
+            Some(security_token.as_str())
+        } else if let Some(ref session_token) = credentials.session_token {
+            Some(session_token.as_str())
         } else {
             None
         };
-        let url = Url::parse(&format!(
+        let mut url = format!(
             "{}{}",
             self.url(),
             &signing::authorization_query_params_no_sig(
-                &self.bucket().access_key().unwrap(),
+                &self.access_key().unwrap(),
                 &self.datetime(),
                 &self.bucket().region(),
                 expiry,
                 custom_headers,
                 token
             )?
-        ))?;
+        );
 
-        Ok(url)
+        // Part of the canonical query string, so covered by the signature
+        if let Command::PresignGet {
+            response_overrides: Some(response_overrides),
+            ..
+        } = self.command()
+        {
+            for (name, value) in response_overrides.query_pairs() {
+                url.push_str(&format!("&{}={}", name, signing::uri_encode(&value, true)));
+            }
+        }
+
+        Ok(Url::parse(&url)?)
     }
 
     fn url(&self) -> Url {
@@ -193,7 +467,7 @@ pub trait Request {
         // Append to url_path
         #[allow(clippy::collapsible_match)]
         match self.command() {
-            Command::InitiateMultipartUpload | Command::ListMultipartUploads { .. } => {
+            Command::InitiateMultipartUpload { .. } | Command::ListMultipartUploads { .. } => {
                 url_str.push_str("?uploads")
             }
             Command::AbortMultipartUpload { upload_id } => {
@@ -203,6 +477,12 @@ pub trait Request {
                 url_str.push_str(&format!("?uploadId={}", upload_id))
             }
             Command::GetObjectTorrent => url_str.push_str("?torrent"),
+            Command::GetObjectPart { part_number } => {
+                url_str.push_str(&format!("?partNumber={}", part_number))
+            }
+            Command::DeleteObjects { .. } => url_str.push_str("?delete"),
+            Command::GetBucketLocation => url_str.push_str("?location"),
+            Command::SelectObjectContent { .. } => url_str.push_str("?select&select-type=2"),
             Command::PutObject { multipart, .. } => {
                 if let Some(multipart) = multipart {
                     url_str.push_str(&multipart.query_string())
@@ -254,7 +534,7 @@ pub trait Request {
                 let mut query_pairs = url.query_pairs_mut();
                 delimiter.map(|d| query_pairs.append_pair("delimiter", &d));
                 if let Some(prefix) = prefix {
-                    query_pairs.append_pair("prefix", prefix);
+                    query_pairs.append_pair("prefix", &prefix);
                 }
                 if let Some(key_marker) = key_marker {
                     query_pairs.append_pair("key-marker", &key_marker);
@@ -263,11 +543,48 @@ pub trait Request {
                     query_pairs.append_pair("max-uploads", max_uploads.to_string().as_str());
                 }
             }
+            Command::ListV1 {
+                prefix,
+                delimiter,
+                marker,
+                max_keys,
+            } => {
+                let mut query_pairs = url.query_pairs_mut();
+                delimiter.map(|d| query_pairs.append_pair("delimiter", &d));
+                query_pairs.append_pair("prefix", &prefix);
+                if let Some(marker) = marker {
+                    query_pairs.append_pair("marker", &marker);
+                }
+                if let Some(max_keys) = max_keys {
+                    query_pairs.append_pair("max-keys", &max_keys.to_string());
+                }
+            }
             Command::PutObjectTagging { .. }
             | Command::GetObjectTagging
             | Command::DeleteObjectTagging => {
                 url.query_pairs_mut().append_pair("tagging", "");
             }
+            Command::GetBucketInventoryConfiguration { id }
+            | Command::DeleteBucketInventoryConfiguration { id } => {
+                url.query_pairs_mut()
+                    .append_pair("inventory", "")
+                    .append_pair("id", id);
+            }
+            Command::PutBucketInventoryConfiguration { configuration } => {
+                url.query_pairs_mut()
+                    .append_pair("inventory", "")
+                    .append_pair("id", &configuration.id);
+            }
+            Command::GetObjectLockConfiguration | Command::PutObjectLockConfiguration { .. } => {
+                url.query_pairs_mut().append_pair("object-lock", "");
+            }
+            Command::ListBucketInventoryConfigurations { continuation_token } => {
+                let mut query_pairs = url.query_pairs_mut();
+                query_pairs.append_pair("inventory", "");
+                if let Some(token) = continuation_token {
+                    query_pairs.append_pair("continuation-token", &token);
+                }
+            }
             _ => {}
         }
 
@@ -283,24 +600,94 @@ pub trait Request {
         )
     }
 
-    fn authorization(&self, headers: &HeaderMap) -> Result<String> {
+    /// `Authorization` header for the request with `headers`, along with what it was signed with
+    /// for V4 signatures.
+    fn authorization(
+        &self,
+        headers: &HeaderMap,
+    ) -> Result<(String, Option<signing::SignedRequest>)> {
+        let bucket = self.bucket();
+        if bucket.signature_version() == SignatureVersion::V2 {
+            let name = bucket.name();
+            let string_to_sign = signing::string_to_sign_v2(
+                &self.command().http_verb().to_string(),
+                &self.url(),
+                headers,
+                bucket.is_subdomain_style().then_some(name.as_str()),
+            );
+            let signature = signing::signature_v2(&self.secret_key().unwrap(), &string_to_sign)?;
+            let authorization =
+                signing::authorization_header_v2(&self.access_key().unwrap(), &signature);
+            return Ok((authorization, None));
+        }
         let canonical_request = self.canonical_request(headers);
         let string_to_sign = self.string_to_sign(&canonical_request);
-        let mut hmac =
-            signing::HmacSha256::new_varkey(&self.signing_key()?).map_err(|e| anyhow! {"{}",e})?;
-        hmac.update(string_to_sign.as_bytes());
-        let signature = hex::encode(hmac.finalize().into_bytes());
+        let signature = signing::signature(&self.signing_key()?, &string_to_sign)?;
         let signed_header = signing::signed_header_string(headers);
-        Ok(signing::authorization_header(
-            &self.bucket().access_key().unwrap(),
+        let authorization = signing::authorization_header(
+            &self.access_key().unwrap(),
             &self.datetime(),
-            &self.bucket().region(),
+            &bucket.region(),
             &signed_header,
             &signature,
-        ))
+        );
+        let signed = signing::SignedRequest {
+            method: self.command().http_verb().to_string(),
+            url: self.url().to_string(),
+            signed_headers: signed_header.split(';').map(str::to_string).collect(),
+            credential_scope: signing::scope_string(&self.datetime(), &bucket.region()),
+            canonical_request,
+            string_to_sign,
+            status: None,
+        };
+        Ok((authorization, Some(signed)))
+    }
+
+    /// Add an attempt to send the request, as `signed`, to the signing log of the bucket, if any,
+    /// along with the status of the response, `None` if there was no response.
+    fn log_attempt(&self, signed: Option<signing::SignedRequest>, status: Option<u16>) {
+        if let (Some(log), Some(mut signed)) = (self.bucket().signing_log(), signed) {
+            signed.status = status;
+            log.push(signed);
+        }
+    }
+
+    /// Log the difference between S3's canonical request and the local one, for a
+    /// `SignatureDoesNotMatch` response returned as is, without the `fail-on-err` feature, which
+    /// attaches it to the error instead, see `error_response`.
+    fn log_signature_mismatch(&self, status: u16, body: &[u8]) {
+        if status == 403 {
+            let _ = self.error_response(status, body);
+        }
+    }
+
+    /// Error for an unsuccessful response. Signature mismatches of V4 signed requests get the
+    /// locally computed canonical request attached, and the difference to S3's logged.
+    fn error_response(&self, status: u16, body: &[u8]) -> S3Error {
+        let error = S3Error::http(status, body);
+        if self.bucket().signature_version() != SignatureVersion::V4 {
+            return error;
+        }
+        if let S3Error::SignatureDoesNotMatch { .. } = error {
+            if let Ok(mut headers) = self.headers() {
+                headers.remove(AUTHORIZATION);
+                headers.remove(DATE);
+                let error = error.with_local_canonical_request(self.canonical_request(&headers));
+                if let Some(diff) = error.canonical_request_diff() {
+                    log::error!("Canonical request diff (-S3, +local):\n{}", diff);
+                }
+                return error;
+            }
+        }
+        error
     }
 
     fn headers(&self) -> Result<HeaderMap> {
+        Ok(self.signed_headers()?.0)
+    }
+
+    /// `headers`, along with what they were signed with, see `log_attempt`.
+    fn signed_headers(&self) -> Result<(HeaderMap, Option<signing::SignedRequest>)> {
         // Generate this once, but it's used in more than one place.
         let sha256 = self.command().sha256();
 
@@ -319,9 +706,14 @@ pub trait Request {
 
         match self.command() {
             Command::ListBucket { .. } => {}
+            Command::ListV1 { .. } => {}
             Command::GetObject => {}
+            Command::GetObjectPart { .. } => {}
             Command::GetObjectTagging => {}
             Command::GetBucketLocation => {}
+            Command::GetBucketInventoryConfiguration { .. } => {}
+            Command::ListBucketInventoryConfigurations { .. } => {}
+            Command::GetObjectLockConfiguration => {}
             _ => {
                 headers.insert(
                     CONTENT_LENGTH,
@@ -334,17 +726,23 @@ pub trait Request {
             HeaderName::from_static("x-amz-content-sha256"),
             sha256.parse().unwrap(),
         );
+        // V2 signatures expect the date in the same format as the `Date` header
+        let amz_date = match self.bucket().signature_version() {
+            SignatureVersion::V2 => self.datetime().format(HTTP_DATE).to_string(),
+            SignatureVersion::V4 => self.long_date(),
+        };
         headers.insert(
             HeaderName::from_static("x-amz-date"),
-            self.long_date().parse().unwrap(),
+            amz_date.parse().unwrap(),
         );
 
-        if let Some(session_token) = self.bucket().session_token() {
+        let credentials = self.credentials();
+        if let Some(session_token) = credentials.session_token {
             headers.insert(
                 HeaderName::from_static("x-amz-security-token"),
-                session_token.to_string().parse().unwrap(),
+                session_token.parse().unwrap(),
             );
-        } else if let Some(security_token) = self.bucket().security_token() {
+        } else if let Some(security_token) = credentials.security_token {
             headers.insert(
                 HeaderName::from_static("x-amz-security-token"),
                 security_token.to_string().parse().unwrap(),
@@ -358,13 +756,80 @@ pub trait Request {
                 HeaderName::from_static("content-md5"),
                 hash.parse().unwrap(),
             );
-        } else if let Command::PutObject { content, .. } = self.command() {
+        } else if let Command::PutObject {
+            content, options, ..
+        } = self.command()
+        {
             let digest = md5::compute(content);
             let hash = base64::encode(digest.as_ref());
             headers.insert(
                 HeaderName::from_static("content-md5"),
                 hash.parse().unwrap(),
             );
+            if let Some(options) = options {
+                options.add_headers(&mut headers)?;
+                if let Some(algorithm) = options.checksum_algorithm() {
+                    headers.insert(algorithm.header(), algorithm.checksum(content).parse()?);
+                }
+            }
+        } else if let Command::PutObjectWithTrailingChecksum {
+            decoded_length,
+            algorithm,
+            ..
+        } = self.command()
+        {
+            headers.insert(
+                HeaderName::from_static("content-encoding"),
+                "aws-chunked".parse()?,
+            );
+            headers.insert(
+                HeaderName::from_static("x-amz-decoded-content-length"),
+                decoded_length.to_string().parse()?,
+            );
+            headers.insert(
+                HeaderName::from_static("x-amz-trailer"),
+                algorithm.header().as_str().parse()?,
+            );
+        } else if let Command::InitiateMultipartUpload {
+            checksum_algorithm: Some(algorithm),
+            ..
+        } = self.command()
+        {
+            headers.insert(
+                HeaderName::from_static("x-amz-checksum-algorithm"),
+                algorithm.to_string().parse()?,
+            );
+        } else if let Command::DeleteObjects { data } = self.command() {
+            // Required by S3 for multi-object deletes
+            let digest = md5::compute(data.to_string().as_bytes());
+            let hash = base64::encode(digest.as_ref());
+            headers.insert(
+                HeaderName::from_static("content-md5"),
+                hash.parse().unwrap(),
+            );
+        } else if let Command::PutObjectLockConfiguration { configuration } = self.command() {
+            // Required by S3 for object lock configurations
+            let digest = md5::compute(configuration.to_string().as_bytes());
+            let hash = base64::encode(digest.as_ref());
+            headers.insert(
+                HeaderName::from_static("content-md5"),
+                hash.parse().unwrap(),
+            );
+        } else if let Command::CopyObject {
+            source_bucket,
+            from,
+            options,
+        } = self.command()
+        {
+            let from = from.strip_prefix('/').unwrap_or(from);
+            let source = format!("/{}/{}", source_bucket, signing::uri_encode(from, false));
+            headers.insert(
+                HeaderName::from_static("x-amz-copy-source"),
+                source.parse()?,
+            );
+            if let Some(options) = options {
+                options.add_headers(&mut headers)?;
+            }
         } else if let Command::UploadPart { content, .. } = self.command() {
             let digest = md5::compute(content);
             let hash = base64::encode(digest.as_ref());
@@ -372,7 +837,7 @@ pub trait Request {
                 HeaderName::from_static("content-md5"),
                 hash.parse().unwrap(),
             );
-        } else if let Command::GetObject {} = self.command() {
+        } else if let Command::GetObject {} | Command::GetObjectPart { .. } = self.command() {
             headers.insert(
                 ACCEPT,
                 "application/octet-stream".to_string().parse().unwrap(),
@@ -391,24 +856,41 @@ pub trait Request {
             }
 
             headers.insert(RANGE, range.parse().unwrap());
+        } else if let Command::GetObjectRangeIfMatch { start, end, etag } = self.command() {
+            headers.insert(
+                ACCEPT,
+                "application/octet-stream".to_string().parse().unwrap(),
+            );
+            headers.insert(RANGE, format!("bytes={}-{}", start, end).parse()?);
+            headers.insert(IF_MATCH, etag.parse()?);
         } else if let Command::CreateBucket { ref config } = self.command() {
             config.add_headers(&mut headers)?;
         }
 
         // This must be last, as it signs the other headers, omitted if no secret key is provided
-        if self.bucket().secret_key().is_some() {
-            let authorization = self.authorization(&headers)?;
+        let mut signed = None;
+        if self.secret_key().is_some() {
+            let (authorization, signed_request) = self.authorization(&headers)?;
             headers.insert(AUTHORIZATION, authorization.parse().unwrap());
+            signed = signed_request;
         }
 
-        // The format of RFC2822 is somewhat malleable, so including it in
+        // The format of the Date header is somewhat malleable, so including it in
         // signed headers can cause signature mismatches. We do include the
         // X-Amz-Date header, so requests are still properly limited to a date
         // range and can't be used again e.g. reply attacks. Adding this header
         // after the generation of the Authorization header leaves it out of
-        // the signed headers.
-        headers.insert(DATE, self.datetime().to_rfc2822().parse().unwrap());
+        // the signed headers. Some S3 compatible servers insist on the RFC 1123
+        // form, with `GMT` rather than the `+0000` of RFC 2822.
+        headers.insert(
+            DATE,
+            self.datetime()
+                .format(HTTP_DATE)
+                .to_string()
+                .parse()
+                .unwrap(),
+        );
 
-        Ok(headers)
+        Ok((headers, signed))
     }
 }
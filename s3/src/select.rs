@@ -0,0 +1,302 @@
+//! [S3 Select](https://docs.aws.amazon.com/AmazonS3/latest/API/API_SelectObjectContent.html)
+//! request bodies and decoding of the event stream it responds with.
+use anyhow::anyhow;
+use anyhow::Result;
+use std::fmt;
+
+use crate::serde_types::xml_escape;
+use crate::utils::crc32;
+
+/// Serialization of the object being queried.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SelectInput {
+    /// `file_header_info` is NONE | USE | IGNORE, `USE` lets the query refer to columns by name.
+    Csv {
+        file_header_info: String,
+        field_delimiter: char,
+    },
+    /// `json_type` is DOCUMENT | LINES
+    Json {
+        json_type: String,
+    },
+    Parquet,
+}
+
+impl SelectInput {
+    /// Comma separated values with a header line naming the columns.
+    pub fn csv_with_header() -> Self {
+        SelectInput::Csv {
+            file_header_info: "USE".to_string(),
+            field_delimiter: ',',
+        }
+    }
+
+    /// One JSON object per line.
+    pub fn json_lines() -> Self {
+        SelectInput::Json {
+            json_type: "LINES".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for SelectInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectInput::Csv {
+                file_header_info,
+                field_delimiter,
+            } => write!(
+                f,
+                "<CSV><FileHeaderInfo>{}</FileHeaderInfo><FieldDelimiter>{}</FieldDelimiter></CSV>",
+                xml_escape(file_header_info),
+                xml_escape(&field_delimiter.to_string())
+            ),
+            SelectInput::Json { json_type } => {
+                write!(f, "<JSON><Type>{}</Type></JSON>", xml_escape(json_type))
+            }
+            SelectInput::Parquet => write!(f, "<Parquet></Parquet>"),
+        }
+    }
+}
+
+/// Serialization of the returned records.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SelectOutput {
+    Csv { field_delimiter: char },
+    Json { record_delimiter: char },
+}
+
+impl fmt::Display for SelectOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectOutput::Csv { field_delimiter } => write!(
+                f,
+                "<CSV><FieldDelimiter>{}</FieldDelimiter></CSV>",
+                xml_escape(&field_delimiter.to_string())
+            ),
+            SelectOutput::Json { record_delimiter } => write!(
+                f,
+                "<JSON><RecordDelimiter>{}</RecordDelimiter></JSON>",
+                xml_escape(&record_delimiter.to_string())
+            ),
+        }
+    }
+}
+
+/// Body of a select request, see `Bucket::select_object_content`
+#[derive(Clone, Debug)]
+pub struct SelectObjectContentData {
+    pub expression: String,
+    pub input: SelectInput,
+    pub output: SelectOutput,
+}
+
+impl fmt::Display for SelectObjectContentData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "<SelectObjectContentRequest xmlns=\"http://s3.amazonaws.com/doc/2006-03-01/\">\
+             <Expression>{}</Expression><ExpressionType>SQL</ExpressionType>\
+             <InputSerialization>{}</InputSerialization>\
+             <OutputSerialization>{}</OutputSerialization>\
+             </SelectObjectContentRequest>",
+            xml_escape(&self.expression),
+            self.input,
+            self.output
+        )
+    }
+}
+
+impl SelectObjectContentData {
+    pub fn len(&self) -> usize {
+        self.to_string().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.to_string().is_empty()
+    }
+}
+
+fn read_u32(data: &[u8], at: usize) -> u32 {
+    u32::from_be_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]])
+}
+
+/// String valued headers of an event stream message, other value types are skipped.
+fn message_headers(mut data: &[u8]) -> Result<Vec<(String, String)>> {
+    let mut headers = Vec::new();
+    while !data.is_empty() {
+        let name_len = data[0] as usize;
+        let name = data
+            .get(1..1 + name_len)
+            .ok_or_else(|| anyhow!("Truncated event stream header"))?;
+        let name = String::from_utf8_lossy(name).to_string();
+        data = &data[1 + name_len..];
+        let value_type = *data
+            .first()
+            .ok_or_else(|| anyhow!("Truncated event stream header"))?;
+        data = &data[1..];
+        // Sizes of the fixed length value types, indexed by type
+        let value_len = match value_type {
+            0 | 1 => 0,
+            2 => 1,
+            3 => 2,
+            4 => 4,
+            5 | 8 => 8,
+            9 => 16,
+            6 | 7 => {
+                if data.len() < 2 {
+                    return Err(anyhow!("Truncated event stream header"));
+                }
+                let len = u16::from_be_bytes([data[0], data[1]]) as usize;
+                data = &data[2..];
+                len
+            }
+            other => return Err(anyhow!("Unknown event stream header type {}", other)),
+        };
+        let value = data
+            .get(..value_len)
+            .ok_or_else(|| anyhow!("Truncated event stream header"))?;
+        if value_type == 7 {
+            headers.push((name, String::from_utf8_lossy(value).to_string()));
+        }
+        data = &data[value_len..];
+    }
+    Ok(headers)
+}
+
+/// Decode the event stream a select request responds with, concatenating the payloads of its
+/// `Records` events. Error events, and streams that stop before the `End` event, are errors.
+pub fn parse_event_stream(mut data: &[u8]) -> Result<Vec<u8>> {
+    let mut records = Vec::new();
+    let mut ended = false;
+    while !data.is_empty() {
+        if data.len() < 16 {
+            return Err(anyhow!("Truncated event stream message"));
+        }
+        let total_len = read_u32(data, 0) as usize;
+        let headers_len = read_u32(data, 4) as usize;
+        if total_len < 16 + headers_len || data.len() < total_len {
+            return Err(anyhow!("Truncated event stream message"));
+        }
+        if crc32(&data[..8]) != read_u32(data, 8)
+            || crc32(&data[..total_len - 4]) != read_u32(data, total_len - 4)
+        {
+            return Err(anyhow!("Event stream message checksum mismatch"));
+        }
+        let headers = message_headers(&data[12..12 + headers_len])?;
+        let payload = &data[12 + headers_len..total_len - 4];
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.as_str())
+        };
+        match (header(":message-type"), header(":event-type")) {
+            (Some("error"), _) => {
+                return Err(anyhow!(
+                    "Select failed with {}: {}",
+                    header(":error-code").unwrap_or("unknown error"),
+                    header(":error-message").unwrap_or_default()
+                ))
+            }
+            (_, Some("Records")) => records.extend_from_slice(payload),
+            (_, Some("End")) => ended = true,
+            // Stats, Progress and Cont(inuation) keep-alive events
+            _ => {}
+        }
+        data = &data[total_len..];
+    }
+    if !ended {
+        return Err(anyhow!("Event stream ended before the End event"));
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_event_stream, SelectInput, SelectObjectContentData, SelectOutput};
+    use crate::utils::crc32;
+
+    fn message(headers: &[(&str, &str)], payload: &[u8]) -> Vec<u8> {
+        let mut encoded_headers = Vec::new();
+        for (name, value) in headers {
+            encoded_headers.push(name.len() as u8);
+            encoded_headers.extend_from_slice(name.as_bytes());
+            encoded_headers.push(7);
+            encoded_headers.extend_from_slice(&(value.len() as u16).to_be_bytes());
+            encoded_headers.extend_from_slice(value.as_bytes());
+        }
+        let total_len = 16 + encoded_headers.len() + payload.len();
+        let mut message = Vec::new();
+        message.extend_from_slice(&(total_len as u32).to_be_bytes());
+        message.extend_from_slice(&(encoded_headers.len() as u32).to_be_bytes());
+        let prelude_crc = crc32(&message);
+        message.extend_from_slice(&prelude_crc.to_be_bytes());
+        message.extend_from_slice(&encoded_headers);
+        message.extend_from_slice(payload);
+        let message_crc = crc32(&message);
+        message.extend_from_slice(&message_crc.to_be_bytes());
+        message
+    }
+
+    fn event(event_type: &str, payload: &[u8]) -> Vec<u8> {
+        message(
+            &[(":message-type", "event"), (":event-type", event_type)],
+            payload,
+        )
+    }
+
+    #[test]
+    fn test_parse_event_stream() {
+        let mut stream = Vec::new();
+        stream.extend(event("Records", b"a,1\n"));
+        stream.extend(event("Cont", b""));
+        stream.extend(event("Records", b"b,2\n"));
+        stream.extend(event("Stats", b"<Stats></Stats>"));
+        stream.extend(event("End", b""));
+        assert_eq!(parse_event_stream(&stream).unwrap(), b"a,1\nb,2\n");
+
+        // Missing End event
+        let stream = event("Records", b"a,1\n");
+        assert!(parse_event_stream(&stream).is_err());
+
+        // Corrupted payload
+        let mut stream = event("Records", b"a,1\n");
+        let last_payload_byte = stream.len() - 5;
+        stream[last_payload_byte] ^= 1;
+        stream.extend(event("End", b""));
+        assert!(parse_event_stream(&stream).is_err());
+
+        let stream = message(
+            &[
+                (":message-type", "error"),
+                (":error-code", "InvalidQuery"),
+                (":error-message", "Unknown column"),
+            ],
+            b"",
+        );
+        let error = parse_event_stream(&stream).unwrap_err();
+        assert!(error.to_string().contains("InvalidQuery"));
+    }
+
+    #[test]
+    fn test_select_body() {
+        let data = SelectObjectContentData {
+            expression: "SELECT s.name FROM S3Object s WHERE s.age > '30'".to_string(),
+            input: SelectInput::csv_with_header(),
+            output: SelectOutput::Json {
+                record_delimiter: '\n',
+            },
+        };
+        assert_eq!(
+            data.to_string(),
+            "<SelectObjectContentRequest xmlns=\"http://s3.amazonaws.com/doc/2006-03-01/\">\
+             <Expression>SELECT s.name FROM S3Object s WHERE s.age &gt; &apos;30&apos;</Expression>\
+             <ExpressionType>SQL</ExpressionType>\
+             <InputSerialization><CSV><FileHeaderInfo>USE</FileHeaderInfo>\
+             <FieldDelimiter>,</FieldDelimiter></CSV></InputSerialization>\
+             <OutputSerialization><JSON><RecordDelimiter>\n</RecordDelimiter></JSON>\
+             </OutputSerialization></SelectObjectContentRequest>"
+        );
+    }
+}
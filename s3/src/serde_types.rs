@@ -1,3 +1,9 @@
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use std::str::FromStr;
+
+use crate::object_ops::ChecksumAlgorithm;
+
 #[derive(Deserialize, Debug)]
 pub struct InitiateMultipartUploadResponse {
     #[serde(rename = "Bucket")]
@@ -19,19 +25,86 @@ pub struct Owner {
     pub id: String,
 }
 
+/// [AWS Documentation](https://docs.aws.amazon.com/AmazonS3/latest/dev/storage-class-intro.html)
+///
+/// Storage class of an object, classes this crate doesn't know about yet are kept as `Unknown`,
+/// so parsing never fails and `to_string` gives back the original value.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum StorageClass {
+    #[default]
+    Standard,
+    ReducedRedundancy,
+    StandardIa,
+    OnezoneIa,
+    IntelligentTiering,
+    Glacier,
+    GlacierIr,
+    DeepArchive,
+    Outposts,
+    Unknown(String),
+}
+
+impl FromStr for StorageClass {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "STANDARD" => StorageClass::Standard,
+            "REDUCED_REDUNDANCY" => StorageClass::ReducedRedundancy,
+            "STANDARD_IA" => StorageClass::StandardIa,
+            "ONEZONE_IA" => StorageClass::OnezoneIa,
+            "INTELLIGENT_TIERING" => StorageClass::IntelligentTiering,
+            "GLACIER" => StorageClass::Glacier,
+            "GLACIER_IR" => StorageClass::GlacierIr,
+            "DEEP_ARCHIVE" => StorageClass::DeepArchive,
+            "OUTPOSTS" => StorageClass::Outposts,
+            other => StorageClass::Unknown(other.to_string()),
+        })
+    }
+}
+
+impl fmt::Display for StorageClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageClass::Standard => write!(f, "STANDARD"),
+            StorageClass::ReducedRedundancy => write!(f, "REDUCED_REDUNDANCY"),
+            StorageClass::StandardIa => write!(f, "STANDARD_IA"),
+            StorageClass::OnezoneIa => write!(f, "ONEZONE_IA"),
+            StorageClass::IntelligentTiering => write!(f, "INTELLIGENT_TIERING"),
+            StorageClass::Glacier => write!(f, "GLACIER"),
+            StorageClass::GlacierIr => write!(f, "GLACIER_IR"),
+            StorageClass::DeepArchive => write!(f, "DEEP_ARCHIVE"),
+            StorageClass::Outposts => write!(f, "OUTPOSTS"),
+            StorageClass::Unknown(other) => write!(f, "{}", other),
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for StorageClass {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = <String as serde::Deserialize>::deserialize(deserializer)?;
+        Ok(s.parse().unwrap_or_else(|e| match e {}))
+    }
+}
+
 /// An individual object in a `ListBucketResult`
 #[derive(Deserialize, Debug, Clone)]
 pub struct Object {
-    #[serde(rename = "LastModified")]
+    #[serde(
+        rename = "LastModified",
+        deserialize_with = "super::deserializer::datetime_deserializer"
+    )]
     /// Date and time the object was last modified.
-    pub last_modified: String,
+    pub last_modified: DateTime<Utc>,
     #[serde(rename = "ETag")]
     /// The entity tag is an MD5 hash of the object. The ETag only reflects changes to the
     /// contents of an object, not its metadata.
     pub e_tag: String,
     #[serde(rename = "StorageClass")]
-    /// STANDARD | STANDARD_IA | REDUCED_REDUNDANCY | GLACIER
-    pub storage_class: String,
+    pub storage_class: StorageClass,
     #[serde(rename = "Key")]
     /// The object's key
     pub key: String,
@@ -50,8 +123,7 @@ pub struct MultipartUpload {
     /// Date and time the multipart upload was initiated
     pub initiated: String,
     #[serde(rename = "StorageClass")]
-    /// STANDARD | STANDARD_IA | REDUCED_REDUNDANCY | GLACIER
-    pub storage_class: String,
+    pub storage_class: StorageClass,
     #[serde(rename = "Key")]
     /// The object's key
     pub key: String,
@@ -94,12 +166,318 @@ pub struct CompleteMultipartUploadData {
     pub parts: Vec<Part>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// Body of a multi-object delete request, see `Bucket::delete_objects`
+#[derive(Debug, Clone)]
+pub struct DeleteObjectsData {
+    pub keys: Vec<String>,
+    /// Only report keys that could not be deleted
+    pub quiet: bool,
+}
+
+pub(crate) fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+impl fmt::Display for DeleteObjectsData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<Delete>")?;
+        if self.quiet {
+            write!(f, "<Quiet>true</Quiet>")?;
+        }
+        for key in &self.keys {
+            write!(f, "<Object><Key>{}</Key></Object>", xml_escape(key))?;
+        }
+        write!(f, "</Delete>")
+    }
+}
+
+impl DeleteObjectsData {
+    pub fn len(&self) -> usize {
+        self.to_string().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+/// An object removed by a multi-object delete
+#[derive(Deserialize, Debug, Clone)]
+pub struct DeletedObject {
+    #[serde(rename = "Key")]
+    pub key: String,
+    #[serde(rename = "VersionId")]
+    pub version_id: Option<String>,
+}
+
+/// An object a multi-object delete failed to remove
+#[derive(Deserialize, Debug, Clone)]
+pub struct DeleteObjectError {
+    #[serde(rename = "Key")]
+    pub key: String,
+    #[serde(rename = "Code")]
+    pub code: String,
+    #[serde(rename = "Message")]
+    pub message: Option<String>,
+    #[serde(rename = "VersionId")]
+    pub version_id: Option<String>,
+}
+
+/// The parsed result of a multi-object delete
+#[derive(Deserialize, Debug, Clone)]
+pub struct DeleteObjectsResult {
+    #[serde(rename = "Deleted", default)]
+    /// Deleted objects, always empty in quiet mode.
+    pub deleted: Vec<DeletedObject>,
+    #[serde(rename = "Error", default)]
+    /// Objects that could not be deleted.
+    pub errors: Vec<DeleteObjectError>,
+}
+
+/// [AWS Documentation](https://docs.aws.amazon.com/AmazonS3/latest/API/API_InventoryConfiguration.html)
+///
+/// Configuration of a daily or weekly S3 Inventory report of the bucket contents, see
+/// `Bucket::put_inventory_configuration`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct InventoryConfiguration {
+    #[serde(rename = "Id")]
+    /// Identifies the configuration within the bucket.
+    pub id: String,
+    #[serde(
+        rename = "IsEnabled",
+        deserialize_with = "super::deserializer::bool_deserializer"
+    )]
+    pub is_enabled: bool,
+    #[serde(rename = "Destination")]
+    pub destination: InventoryDestination,
+    #[serde(rename = "Filter")]
+    /// Only report objects whose key starts with this prefix.
+    pub filter: Option<InventoryFilter>,
+    #[serde(rename = "IncludedObjectVersions")]
+    /// All | Current
+    pub included_object_versions: String,
+    #[serde(rename = "OptionalFields")]
+    pub optional_fields: Option<InventoryOptionalFields>,
+    #[serde(rename = "Schedule")]
+    pub schedule: InventorySchedule,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct InventoryDestination {
+    #[serde(rename = "S3BucketDestination")]
+    pub s3_bucket_destination: InventoryS3BucketDestination,
+}
+
+/// Where the inventory reports are written
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct InventoryS3BucketDestination {
+    #[serde(rename = "AccountId")]
+    /// Account ID that owns the destination bucket.
+    pub account_id: Option<String>,
+    #[serde(rename = "Bucket")]
+    /// ARN of the destination bucket, `arn:aws:s3:::bucket-name`.
+    pub bucket: String,
+    #[serde(rename = "Format")]
+    /// CSV | ORC | Parquet
+    pub format: String,
+    #[serde(rename = "Prefix")]
+    /// Prefix of the report keys in the destination bucket.
+    pub prefix: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct InventoryFilter {
+    #[serde(rename = "Prefix")]
+    pub prefix: String,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct InventoryOptionalFields {
+    #[serde(rename = "Field", default)]
+    /// Size | LastModifiedDate | StorageClass | ETag | IsMultipartUploaded | ReplicationStatus |
+    /// EncryptionStatus | ObjectLockRetainUntilDate | ObjectLockMode |
+    /// ObjectLockLegalHoldStatus | IntelligentTieringAccessTier
+    pub fields: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct InventorySchedule {
+    #[serde(rename = "Frequency")]
+    /// Daily | Weekly
+    pub frequency: String,
+}
+
+impl fmt::Display for InventoryConfiguration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "<InventoryConfiguration xmlns=\"http://s3.amazonaws.com/doc/2006-03-01/\">"
+        )?;
+        write!(f, "<Id>{}</Id>", xml_escape(&self.id))?;
+        write!(f, "<IsEnabled>{}</IsEnabled>", self.is_enabled)?;
+        let destination = &self.destination.s3_bucket_destination;
+        write!(f, "<Destination><S3BucketDestination>")?;
+        if let Some(ref account_id) = destination.account_id {
+            write!(f, "<AccountId>{}</AccountId>", xml_escape(account_id))?;
+        }
+        write!(f, "<Bucket>{}</Bucket>", xml_escape(&destination.bucket))?;
+        write!(f, "<Format>{}</Format>", xml_escape(&destination.format))?;
+        if let Some(ref prefix) = destination.prefix {
+            write!(f, "<Prefix>{}</Prefix>", xml_escape(prefix))?;
+        }
+        write!(f, "</S3BucketDestination></Destination>")?;
+        if let Some(ref filter) = self.filter {
+            write!(
+                f,
+                "<Filter><Prefix>{}</Prefix></Filter>",
+                xml_escape(&filter.prefix)
+            )?;
+        }
+        write!(
+            f,
+            "<IncludedObjectVersions>{}</IncludedObjectVersions>",
+            xml_escape(&self.included_object_versions)
+        )?;
+        if let Some(ref optional_fields) = self.optional_fields {
+            write!(f, "<OptionalFields>")?;
+            for field in &optional_fields.fields {
+                write!(f, "<Field>{}</Field>", xml_escape(field))?;
+            }
+            write!(f, "</OptionalFields>")?;
+        }
+        write!(
+            f,
+            "<Schedule><Frequency>{}</Frequency></Schedule>",
+            xml_escape(&self.schedule.frequency)
+        )?;
+        write!(f, "</InventoryConfiguration>")
+    }
+}
+
+/// The parsed result of listing the inventory configurations of a bucket
+#[derive(Deserialize, Debug, Clone)]
+pub struct ListInventoryConfigurationsResult {
+    #[serde(rename = "InventoryConfiguration", default)]
+    pub inventory_configurations: Vec<InventoryConfiguration>,
+    #[serde(
+        rename = "IsTruncated",
+        deserialize_with = "super::deserializer::bool_deserializer"
+    )]
+    pub is_truncated: bool,
+    #[serde(rename = "ContinuationToken")]
+    pub continuation_token: Option<String>,
+    #[serde(rename = "NextContinuationToken")]
+    pub next_continuation_token: Option<String>,
+}
+
+/// Part of a multipart upload, as listed when completing it. The checksum matching the
+/// algorithm the upload was initiated with has to be set for S3 to verify the assembled object.
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct Part {
     #[serde(rename = "PartNumber")]
     pub part_number: u32,
     #[serde(rename = "ETag")]
     pub etag: String,
+    #[serde(rename = "ChecksumCRC32", skip_serializing_if = "Option::is_none")]
+    pub checksum_crc32: Option<String>,
+    #[serde(rename = "ChecksumCRC32C", skip_serializing_if = "Option::is_none")]
+    pub checksum_crc32c: Option<String>,
+    #[serde(rename = "ChecksumSHA256", skip_serializing_if = "Option::is_none")]
+    pub checksum_sha256: Option<String>,
+}
+
+impl Part {
+    /// Set the checksum of the part for `algorithm`, see `ChecksumAlgorithm::checksum`.
+    pub fn set_checksum(&mut self, algorithm: ChecksumAlgorithm, checksum: String) {
+        match algorithm {
+            ChecksumAlgorithm::Crc32 => self.checksum_crc32 = Some(checksum),
+            ChecksumAlgorithm::Crc32c => self.checksum_crc32c = Some(checksum),
+            ChecksumAlgorithm::Sha256 => self.checksum_sha256 = Some(checksum),
+        }
+    }
+}
+
+/// [AWS Documentation](https://docs.aws.amazon.com/AmazonS3/latest/API/API_ObjectLockConfiguration.html)
+///
+/// Bucket level object lock configuration, with the retention applied by default to new objects.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ObjectLockConfiguration {
+    #[serde(rename = "ObjectLockEnabled")]
+    /// Enabled
+    pub object_lock_enabled: Option<String>,
+    #[serde(rename = "Rule")]
+    pub rule: Option<ObjectLockRule>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ObjectLockRule {
+    #[serde(rename = "DefaultRetention")]
+    pub default_retention: DefaultRetention,
+}
+
+/// Retention of new objects, for either `days` or `years`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct DefaultRetention {
+    #[serde(rename = "Mode")]
+    /// GOVERNANCE | COMPLIANCE
+    pub mode: String,
+    #[serde(rename = "Days")]
+    pub days: Option<u32>,
+    #[serde(rename = "Years")]
+    pub years: Option<u32>,
+}
+
+impl fmt::Display for ObjectLockConfiguration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "<ObjectLockConfiguration xmlns=\"http://s3.amazonaws.com/doc/2006-03-01/\">"
+        )?;
+        if let Some(ref object_lock_enabled) = self.object_lock_enabled {
+            write!(
+                f,
+                "<ObjectLockEnabled>{}</ObjectLockEnabled>",
+                xml_escape(object_lock_enabled)
+            )?;
+        }
+        if let Some(ref rule) = self.rule {
+            let retention = &rule.default_retention;
+            write!(
+                f,
+                "<Rule><DefaultRetention><Mode>{}</Mode>",
+                xml_escape(&retention.mode)
+            )?;
+            if let Some(days) = retention.days {
+                write!(f, "<Days>{}</Days>", days)?;
+            }
+            if let Some(years) = retention.years {
+                write!(f, "<Years>{}</Years>", years)?;
+            }
+            write!(f, "</DefaultRetention></Rule>")?;
+        }
+        write!(f, "</ObjectLockConfiguration>")
+    }
+}
+
+/// The parsed result of a server side copy
+#[derive(Deserialize, Debug, Clone)]
+pub struct CopyObjectResult {
+    #[serde(rename = "ETag")]
+    /// The entity tag of the new object.
+    pub e_tag: String,
+    #[serde(rename = "LastModified")]
+    pub last_modified: String,
+    /// Version of the new object, from the `x-amz-version-id` header, on versioned buckets.
+    #[serde(skip)]
+    pub version_id: Option<String>,
+    /// Version of the object copied, from the `x-amz-copy-source-version-id` header, on
+    /// versioned buckets.
+    #[serde(skip)]
+    pub copy_source_version_id: Option<String>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -111,8 +489,8 @@ pub struct BucketLocationResult {
 /// The parsed result of a s3 bucket listing
 #[derive(Deserialize, Debug, Clone)]
 pub struct ListBucketResult {
-    #[serde(rename = "Name")]
-    /// Name of the bucket.
+    #[serde(rename = "Name", default)]
+    /// Name of the bucket, empty if the server omits it.
     pub name: String,
     #[serde(rename = "NextMarker")]
     /// When the response is truncated (that is, the IsTruncated element value in the response
@@ -120,14 +498,15 @@ pub struct ListBucketResult {
     /// to get next set of objects. Amazon S3 lists objects in UTF-8 character encoding in
     /// lexicographical order.
     pub next_marker: Option<String>,
-    #[serde(rename = "Delimiter")]
+    #[serde(rename = "Delimiter", default)]
     /// A delimiter is a character you use to group keys.
     pub delimiter: Option<String>,
     #[serde(rename = "MaxKeys")]
     /// Sets the maximum number of keys returned in the response body.
     pub max_keys: i32,
-    #[serde(rename = "Prefix")]
-    /// Limits the response to keys that begin with the specified prefix.
+    #[serde(rename = "Prefix", default)]
+    /// Limits the response to keys that begin with the specified prefix, empty if the server
+    /// omits it.
     pub prefix: String,
     #[serde(rename = "Marker")]
     /// Indicates where in the bucket listing begins. Marker is included in the response if
@@ -155,6 +534,151 @@ pub struct ListBucketResult {
     pub common_prefixes: Option<Vec<CommonPrefix>>,
 }
 
+/// Tags of an object, the body of `put_object_tagging` and `get_object_tagging`
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tagging {
+    #[serde(rename = "TagSet", default)]
+    pub tag_set: TagSet,
+}
+
+impl Tagging {
+    pub fn new<S: AsRef<str>>(tags: &[(S, S)]) -> Self {
+        Tagging {
+            tag_set: TagSet {
+                tags: tags
+                    .iter()
+                    .map(|(key, value)| Tag::new(key.as_ref(), value.as_ref()))
+                    .collect(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Tagging {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<Tagging><TagSet>")?;
+        for tag in &self.tag_set.tags {
+            write!(
+                f,
+                "<Tag><Key>{}</Key><Value>{}</Value></Tag>",
+                xml_escape(&tag.key),
+                xml_escape(&tag.value)
+            )?;
+        }
+        write!(f, "</TagSet></Tagging>")
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagSet {
+    #[serde(rename = "Tag", default)]
+    pub tags: Vec<Tag>,
+}
+
+/// A single object tag
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Tag {
+    #[serde(rename = "Key")]
+    key: String,
+    #[serde(rename = "Value", default)]
+    value: String,
+}
+
+impl Tag {
+    pub fn new(key: &str, value: &str) -> Self {
+        Tag {
+            key: key.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    pub fn key(&self) -> String {
+        self.key.to_owned()
+    }
+
+    pub fn value(&self) -> String {
+        self.value.to_owned()
+    }
+}
+
+/// The parsed result of a s3 bucket listing with the older, marker based, ListObjects API, see
+/// `Bucket::list_v1`
+#[derive(Deserialize, Debug, Clone)]
+pub struct ListBucketResultV1 {
+    #[serde(rename = "Name", default)]
+    /// Name of the bucket, empty if the server omits it.
+    pub name: String,
+    #[serde(rename = "Prefix", default)]
+    /// Limits the response to keys that begin with the specified prefix, empty if the server
+    /// omits it.
+    pub prefix: String,
+    #[serde(rename = "Marker", default)]
+    /// Key the listing started after, as sent with the request.
+    pub marker: Option<String>,
+    #[serde(rename = "NextMarker", default)]
+    /// Key to continue a truncated listing after, only returned when a delimiter was used.
+    pub next_marker: Option<String>,
+    #[serde(rename = "Delimiter", default)]
+    /// A delimiter is a character you use to group keys.
+    pub delimiter: Option<String>,
+    #[serde(rename = "MaxKeys")]
+    /// Sets the maximum number of keys returned in the response body.
+    pub max_keys: i32,
+    #[serde(rename = "EncodingType")]
+    /// Specifies the encoding method to used
+    pub encoding_type: Option<String>,
+    #[serde(
+        rename = "IsTruncated",
+        deserialize_with = "super::deserializer::bool_deserializer"
+    )]
+    ///  Specifies whether (true) or not (false) all of the results were returned.
+    pub is_truncated: bool,
+    #[serde(rename = "Contents", default)]
+    /// Metadata about each object returned.
+    pub contents: Vec<Object>,
+    #[serde(rename = "CommonPrefixes", default)]
+    /// All of the keys rolled up into a common prefix count as a single return when
+    /// calculating the number of returns.
+    pub common_prefixes: Option<Vec<CommonPrefix>>,
+}
+
+impl ListBucketResultV1 {
+    /// Marker to request the next page with, `None` if the listing is complete. Servers only
+    /// return `NextMarker` with a delimiter, otherwise the last key of this page is used.
+    pub fn next_page_marker(&self) -> Option<String> {
+        if !self.is_truncated {
+            return None;
+        }
+        self.next_marker.clone().or_else(|| {
+            let last_key = self.contents.last().map(|object| &object.key);
+            let last_prefix = self
+                .common_prefixes
+                .as_ref()
+                .and_then(|prefixes| prefixes.last())
+                .map(|prefix| &prefix.prefix);
+            last_key.max(last_prefix).cloned()
+        })
+    }
+}
+
+impl From<ListBucketResultV1> for ListBucketResult {
+    fn from(result: ListBucketResultV1) -> Self {
+        ListBucketResult {
+            name: result.name,
+            next_marker: result.next_marker,
+            delimiter: result.delimiter,
+            max_keys: result.max_keys,
+            prefix: result.prefix,
+            marker: result.marker,
+            encoding_type: result.encoding_type,
+            is_truncated: result.is_truncated,
+            next_continuation_token: None,
+            contents: result.contents,
+            common_prefixes: result.common_prefixes,
+        }
+    }
+}
+
 /// The parsed result of a s3 bucket listing of uploads
 #[derive(Deserialize, Debug, Clone)]
 pub struct ListMultipartUploadsResult {
@@ -220,9 +744,9 @@ pub struct HeadObjectResult {
     #[serde(rename = "ContentLanguage")]
     /// The language the content is in.
     pub content_language: Option<String>,
-    #[serde(rename = "ContentLength")]
-    /// Size of the body in bytes.
-    pub content_length: Option<i64>,
+    #[serde(rename = "ContentLength", default)]
+    /// Size of the body in bytes, `0` if the response carried no `Content-Length`.
+    pub content_length: u64,
     #[serde(rename = "ContentType")]
     /// A standard MIME type describing the format of the object data.
     pub content_type: Option<String>,
@@ -231,7 +755,7 @@ pub struct HeadObjectResult {
     pub delete_marker: Option<bool>,
     #[serde(rename = "ETag")]
     /// An ETag is an opaque identifier assigned by a web server to a specific version of a resource found at a URL.
-    pub e_tag: Option<String>,
+    pub etag: Option<String>,
     #[serde(rename = "Expiration")]
     /// If the object expiration is configured, the response includes this header. It includes the expiry-date and rule-id key-value pairs providing object expiration information.
     /// The value of the rule-id is URL encoded.
@@ -239,12 +763,16 @@ pub struct HeadObjectResult {
     #[serde(rename = "Expires")]
     /// The date and time at which the object is no longer cacheable.
     pub expires: Option<String>,
-    #[serde(rename = "LastModified")]
-    /// Last modified date of the object
-    pub last_modified: Option<String>,
+    #[serde(
+        rename = "LastModified",
+        default,
+        deserialize_with = "super::deserializer::optional_http_date_deserializer"
+    )]
+    /// Last modified date of the object, `None` if absent or not a valid date.
+    pub last_modified: Option<DateTime<Utc>>,
     #[serde(rename = "Metadata", default)]
-    /// A map of metadata to store with the object in S3.
-    pub metadata: Option<::std::collections::HashMap<String, String>>,
+    /// The user metadata of the object, from its `x-amz-meta-*` headers.
+    pub metadata: ::std::collections::HashMap<String, String>,
     #[serde(rename = "MissingMeta")]
     /// This is set to the number of metadata entries not returned in x-amz-meta headers. This can happen if you create metadata using an API like SOAP that supports more flexible metadata than
     /// the REST API. For example, using SOAP, you can create metadata whose values are not legal HTTP headers.
@@ -287,7 +815,7 @@ pub struct HeadObjectResult {
     pub server_side_encryption: Option<String>,
     #[serde(rename = "StorageClass")]
     /// Provides storage class information of the object. Amazon S3 returns this header for all objects except for S3 Standard storage class objects.
-    pub storage_class: Option<String>,
+    pub storage_class: Option<StorageClass>,
     #[serde(rename = "VersionId")]
     /// Version of the object.
     pub version_id: Option<String>,
@@ -296,12 +824,596 @@ pub struct HeadObjectResult {
     pub website_redirect_location: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+impl HeadObjectResult {
+    /// Progress of restoring an archived object, parsed from the `x-amz-restore` header.
+    pub fn restore_status(&self) -> anyhow::Result<RestoreStatus> {
+        let restore = match self.restore {
+            Some(ref restore) => restore,
+            None => return Ok(RestoreStatus::NotRequested),
+        };
+        // ongoing-request="false", expiry-date="Fri, 21 Dec 2012 00:00:00 GMT"
+        let value = |key: &str| {
+            let start = restore.find(&format!("{}=\"", key))? + key.len() + 2;
+            let len = restore[start..].find('"')?;
+            Some(&restore[start..start + len])
+        };
+        match value("ongoing-request") {
+            Some("true") => Ok(RestoreStatus::InProgress),
+            Some("false") => value("expiry-date")
+                .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+                .map(|expiry| RestoreStatus::Completed {
+                    expiry: expiry.with_timezone(&Utc),
+                })
+                .ok_or_else(|| anyhow!("Invalid x-amz-restore header {:?}", restore)),
+            _ => Err(anyhow!("Invalid x-amz-restore header {:?}", restore)),
+        }
+    }
+}
+
+/// [AWS Documentation](https://docs.aws.amazon.com/AmazonS3/latest/API/API_HeadObject.html#API_HeadObject_ResponseSyntax)
+///
+/// Restore progress of an archived object, see `Bucket::restore_status`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RestoreStatus {
+    NotRequested,
+    InProgress,
+    /// The restored copy can be read until `expiry`.
+    Completed {
+        expiry: DateTime<Utc>,
+    },
+}
+
+/// [AWS Documentation](https://docs.aws.amazon.com/AmazonS3/latest/userguide/acl-overview.html#permissions)
+///
+/// Permission an ACL grant gives, `Write` only applies to buckets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Permission {
+    Read,
+    Write,
+    ReadAcp,
+    WriteAcp,
+    FullControl,
+}
+
+impl FromStr for Permission {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "READ" => Permission::Read,
+            "WRITE" => Permission::Write,
+            "READ_ACP" => Permission::ReadAcp,
+            "WRITE_ACP" => Permission::WriteAcp,
+            "FULL_CONTROL" => Permission::FullControl,
+            other => return Err(anyhow!("Unknown ACL permission {}", other)),
+        })
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Permission::Read => write!(f, "READ"),
+            Permission::Write => write!(f, "WRITE"),
+            Permission::ReadAcp => write!(f, "READ_ACP"),
+            Permission::WriteAcp => write!(f, "WRITE_ACP"),
+            Permission::FullControl => write!(f, "FULL_CONTROL"),
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Permission {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = <String as serde::Deserialize>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// [AWS Documentation](https://docs.aws.amazon.com/AmazonS3/latest/API/API_Grantee.html)
+///
+/// Account an ACL grant applies to, by canonical user id, predefined group URI or email address.
+///
+/// `to_string` gives the `<Grantee>` element, with its `xsi:type`. When parsing, the variant is
+/// picked from the child elements, as not all S3 compatible servers set `xsi:type`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Grantee {
+    CanonicalUser {
+        id: String,
+        display_name: Option<String>,
+    },
+    /// URI of a predefined group, like `Grantee::ALL_USERS`.
+    Group(String),
+    /// Email address of an account, only supported in some of the older regions.
+    Email(String),
+}
+
+impl Grantee {
+    pub const ALL_USERS: &'static str = "http://acs.amazonaws.com/groups/global/AllUsers";
+    pub const AUTHENTICATED_USERS: &'static str =
+        "http://acs.amazonaws.com/groups/global/AuthenticatedUsers";
+    pub const LOG_DELIVERY: &'static str = "http://acs.amazonaws.com/groups/s3/LogDelivery";
+
+    pub fn group(uri: &str) -> Self {
+        Grantee::Group(uri.to_string())
+    }
+}
+
+const XSI_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema-instance";
+
+impl fmt::Display for Grantee {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let xsi_type = match self {
+            Grantee::CanonicalUser { .. } => "CanonicalUser",
+            Grantee::Group(_) => "Group",
+            Grantee::Email(_) => "AmazonCustomerByEmail",
+        };
+        write!(
+            f,
+            "<Grantee xmlns:xsi=\"{}\" xsi:type=\"{}\">",
+            XSI_NAMESPACE, xsi_type
+        )?;
+        match self {
+            Grantee::CanonicalUser { id, display_name } => {
+                write!(f, "<ID>{}</ID>", xml_escape(id))?;
+                if let Some(display_name) = display_name {
+                    write!(f, "<DisplayName>{}</DisplayName>", xml_escape(display_name))?;
+                }
+            }
+            Grantee::Group(uri) => write!(f, "<URI>{}</URI>", xml_escape(uri))?,
+            Grantee::Email(email) => {
+                write!(f, "<EmailAddress>{}</EmailAddress>", xml_escape(email))?
+            }
+        }
+        write!(f, "</Grantee>")
+    }
+}
+
+#[derive(Deserialize)]
+struct GranteeElement {
+    #[serde(rename = "ID")]
+    id: Option<String>,
+    #[serde(rename = "DisplayName")]
+    display_name: Option<String>,
+    #[serde(rename = "URI")]
+    uri: Option<String>,
+    #[serde(rename = "EmailAddress")]
+    email_address: Option<String>,
+}
+
+impl<'de> serde::Deserialize<'de> for Grantee {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let element = GranteeElement::deserialize(deserializer)?;
+        match element {
+            GranteeElement {
+                id: Some(id),
+                display_name,
+                ..
+            } => Ok(Grantee::CanonicalUser { id, display_name }),
+            GranteeElement { uri: Some(uri), .. } => Ok(Grantee::Group(uri)),
+            GranteeElement {
+                email_address: Some(email),
+                ..
+            } => Ok(Grantee::Email(email)),
+            _ => Err(serde::de::Error::custom(
+                "Grantee without ID, URI or EmailAddress",
+            )),
+        }
+    }
+}
+
+/// A single entry of an access control list
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Grant {
+    #[serde(rename = "Grantee")]
+    pub grantee: Grantee,
+    #[serde(rename = "Permission")]
+    pub permission: Permission,
+}
+
+impl fmt::Display for Grant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "<Grant>{}<Permission>{}</Permission></Grant>",
+            self.grantee, self.permission
+        )
+    }
+}
+
+/// The `<Error>` document of an unsuccessful response, see `S3Error::aws_error`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct AwsError {
+    /// Error code, like `NoSuchKey` or `AccessDenied`.
     #[serde(rename = "Code")]
     pub code: String,
-    #[serde(rename = "Message")]
+    #[serde(rename = "Message", default)]
     pub message: String,
-    #[serde(rename = "RequestId")]
+    #[serde(rename = "RequestId", default)]
     pub request_id: String,
+    /// Bucket or object the error is about, not sent for every error.
+    #[serde(rename = "Resource")]
+    pub resource: Option<String>,
+}
+
+impl AwsError {
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn request_id(&self) -> &str {
+        &self.request_id
+    }
+
+    pub fn resource(&self) -> Option<&str> {
+        self.resource.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        AwsError, DeleteObjectsData, DeleteObjectsResult, Grant, Grantee, HeadObjectResult,
+        InventoryConfiguration, ListBucketResult, ListBucketResultV1,
+        ListInventoryConfigurationsResult, ListMultipartUploadsResult, ObjectLockConfiguration,
+        Permission, RestoreStatus, StorageClass, Tag, Tagging,
+    };
+    use chrono::{TimeZone, Utc};
+    use serde_xml_rs as serde_xml;
+
+    #[test]
+    fn test_list_bucket_result_echoes_query() {
+        let xml = "<ListBucketResult xmlns=\"http://s3.amazonaws.com/doc/2006-03-01/\">\
+                   <Name>rust-s3</Name><Prefix>logs/</Prefix><Delimiter>/</Delimiter>\
+                   <MaxKeys>1000</MaxKeys><IsTruncated>false</IsTruncated>\
+                   </ListBucketResult>";
+        let result: ListBucketResult = serde_xml::from_str(xml).unwrap();
+        assert_eq!(result.name, "rust-s3");
+        assert_eq!(result.prefix, "logs/");
+        assert_eq!(result.delimiter.as_deref(), Some("/"));
+
+        // Some S3 compatible servers leave them out
+        let xml = "<ListBucketResult><MaxKeys>1000</MaxKeys>\
+                   <IsTruncated>false</IsTruncated></ListBucketResult>";
+        let result: ListBucketResult = serde_xml::from_str(xml).unwrap();
+        assert_eq!(result.name, "");
+        assert_eq!(result.prefix, "");
+        assert_eq!(result.delimiter, None);
+    }
+
+    #[test]
+    fn test_list_bucket_result_v1() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+    <Name>rust-s3</Name>
+    <Prefix>logs/</Prefix>
+    <Marker></Marker>
+    <MaxKeys>2</MaxKeys>
+    <IsTruncated>true</IsTruncated>
+    <Contents>
+        <Key>logs/a.txt</Key>
+        <LastModified>2009-10-12T17:50:30.000Z</LastModified>
+        <ETag>&quot;fba9dede5f27731c9771645a39863328&quot;</ETag>
+        <Size>434234</Size>
+        <StorageClass>STANDARD</StorageClass>
+        <Owner>
+            <ID>75aa57f09aa0c8caeab4f8c24e99d10f8e7faeebf76c078efc7c6caea54ba06a</ID>
+            <DisplayName>mtd@amazon.com</DisplayName>
+        </Owner>
+    </Contents>
+    <Contents>
+        <Key>logs/b.txt</Key>
+        <LastModified>2009-10-12T17:50:31.000Z</LastModified>
+        <ETag>&quot;828ef3fdfa96f00ad9f27c383fc9ac7f&quot;</ETag>
+        <Size>5</Size>
+        <StorageClass>STANDARD</StorageClass>
+    </Contents>
+</ListBucketResult>"#;
+        let result: ListBucketResultV1 = serde_xml::from_str(xml).unwrap();
+        assert_eq!(result.name, "rust-s3");
+        assert_eq!(result.max_keys, 2);
+        assert!(result.is_truncated);
+        assert_eq!(result.next_marker, None);
+        assert_eq!(result.contents.len(), 2);
+        // Without a delimiter there is no NextMarker, the listing continues after the last key
+        assert_eq!(result.next_page_marker().as_deref(), Some("logs/b.txt"));
+
+        let xml = "<ListBucketResult><Name>rust-s3</Name><Prefix></Prefix><Marker>a</Marker>\
+                   <NextMarker>logs/</NextMarker><MaxKeys>1</MaxKeys><Delimiter>/</Delimiter>\
+                   <IsTruncated>true</IsTruncated>\
+                   <CommonPrefixes><Prefix>logs/</Prefix></CommonPrefixes></ListBucketResult>";
+        let result: ListBucketResultV1 = serde_xml::from_str(xml).unwrap();
+        assert_eq!(result.next_page_marker().as_deref(), Some("logs/"));
+        let result = ListBucketResult::from(result);
+        assert_eq!(result.marker.as_deref(), Some("a"));
+        assert_eq!(result.common_prefixes.unwrap()[0].prefix, "logs/");
+        assert_eq!(result.next_continuation_token, None);
+    }
+
+    #[test]
+    fn test_tagging() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Tagging xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+   <TagSet>
+      <Tag>
+         <Key>project</Key>
+         <Value>rust-s3</Value>
+      </Tag>
+      <Tag>
+         <Key>cost-center</Key>
+         <Value>42</Value>
+      </Tag>
+   </TagSet>
+</Tagging>"#;
+        let tagging: Tagging = serde_xml::from_str(xml).unwrap();
+        assert_eq!(
+            tagging.tag_set.tags,
+            vec![
+                Tag::new("project", "rust-s3"),
+                Tag::new("cost-center", "42")
+            ]
+        );
+
+        let tagging: Tagging = serde_xml::from_str("<Tagging><TagSet></TagSet></Tagging>").unwrap();
+        assert!(tagging.tag_set.tags.is_empty());
+
+        assert_eq!(
+            Tagging::new(&[("a&b", "<c>")]).to_string(),
+            "<Tagging><TagSet><Tag><Key>a&amp;b</Key><Value>&lt;c&gt;</Value></Tag>\
+             </TagSet></Tagging>"
+        );
+    }
+
+    #[test]
+    fn test_restore_status() {
+        let status = |restore: Option<&str>| {
+            HeadObjectResult {
+                restore: restore.map(str::to_string),
+                ..Default::default()
+            }
+            .restore_status()
+        };
+        assert_eq!(status(None).unwrap(), RestoreStatus::NotRequested);
+        assert_eq!(
+            status(Some("ongoing-request=\"true\"")).unwrap(),
+            RestoreStatus::InProgress
+        );
+        assert_eq!(
+            status(Some(
+                "ongoing-request=\"false\", expiry-date=\"Fri, 21 Dec 2012 00:00:00 GMT\""
+            ))
+            .unwrap(),
+            RestoreStatus::Completed {
+                expiry: Utc.ymd(2012, 12, 21).and_hms(0, 0, 0)
+            }
+        );
+        assert!(status(Some("ongoing-request=\"false\"")).is_err());
+    }
+
+    #[test]
+    fn test_delete_objects_body() {
+        let data = DeleteObjectsData {
+            keys: vec!["a.txt".to_string(), "b&<c>.txt".to_string()],
+            quiet: true,
+        };
+        assert_eq!(
+            data.to_string(),
+            "<Delete><Quiet>true</Quiet>\
+             <Object><Key>a.txt</Key></Object>\
+             <Object><Key>b&amp;&lt;c&gt;.txt</Key></Object>\
+             </Delete>"
+        );
+
+        let data = DeleteObjectsData {
+            keys: vec!["a.txt".to_string()],
+            quiet: false,
+        };
+        assert_eq!(
+            data.to_string(),
+            "<Delete><Object><Key>a.txt</Key></Object></Delete>"
+        );
+    }
+
+    #[test]
+    fn test_parse_delete_objects_result() {
+        // The XML declaration has to come first, without leading whitespace
+        let result_string = r###"<?xml version="1.0" encoding="UTF-8"?>
+            <DeleteResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                <Deleted><Key>a.txt</Key></Deleted>
+                <Error><Key>b.txt</Key><Code>AccessDenied</Code><Message>Access Denied</Message></Error>
+            </DeleteResult>
+        "###;
+        let result: DeleteObjectsResult = serde_xml::from_reader(result_string.as_bytes()).unwrap();
+        assert_eq!(result.deleted.len(), 1);
+        assert_eq!(result.deleted[0].key, "a.txt");
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].code, "AccessDenied");
+
+        let result: DeleteObjectsResult =
+            serde_xml::from_reader(r#"<DeleteResult></DeleteResult>"#.as_bytes()).unwrap();
+        assert!(result.deleted.is_empty());
+        assert!(result.errors.is_empty());
+    }
+
+    #[test]
+    fn test_inventory_configuration_round_trip() {
+        let result_string = r###"<?xml version="1.0" encoding="UTF-8"?>
+            <ListInventoryConfigurationsResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                <InventoryConfiguration>
+                    <Id>daily</Id>
+                    <IsEnabled>true</IsEnabled>
+                    <Destination>
+                        <S3BucketDestination>
+                            <Bucket>arn:aws:s3:::inventory-reports</Bucket>
+                            <Format>CSV</Format>
+                            <Prefix>reports</Prefix>
+                        </S3BucketDestination>
+                    </Destination>
+                    <Filter><Prefix>data/</Prefix></Filter>
+                    <IncludedObjectVersions>Current</IncludedObjectVersions>
+                    <OptionalFields><Field>Size</Field><Field>ETag</Field></OptionalFields>
+                    <Schedule><Frequency>Daily</Frequency></Schedule>
+                </InventoryConfiguration>
+                <IsTruncated>false</IsTruncated>
+            </ListInventoryConfigurationsResult>
+        "###;
+        let result: ListInventoryConfigurationsResult =
+            serde_xml::from_reader(result_string.as_bytes()).unwrap();
+        assert!(!result.is_truncated);
+        assert_eq!(result.inventory_configurations.len(), 1);
+        let configuration = &result.inventory_configurations[0];
+        assert_eq!(configuration.id, "daily");
+        assert_eq!(
+            configuration.optional_fields.as_ref().unwrap().fields,
+            vec!["Size", "ETag"]
+        );
+
+        let xml = configuration.to_string();
+        assert!(xml.contains(
+            "<Destination><S3BucketDestination><Bucket>arn:aws:s3:::inventory-reports</Bucket>\
+             <Format>CSV</Format><Prefix>reports</Prefix></S3BucketDestination></Destination>"
+        ));
+        let parsed: InventoryConfiguration = serde_xml::from_reader(xml.as_bytes()).unwrap();
+        assert_eq!(&parsed, configuration);
+    }
+
+    #[test]
+    fn test_object_lock_configuration_round_trip() {
+        let result_string = r###"<?xml version="1.0" encoding="UTF-8"?>
+            <ObjectLockConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                <ObjectLockEnabled>Enabled</ObjectLockEnabled>
+                <Rule><DefaultRetention><Mode>COMPLIANCE</Mode><Days>30</Days></DefaultRetention></Rule>
+            </ObjectLockConfiguration>
+        "###;
+        let configuration: ObjectLockConfiguration =
+            serde_xml::from_reader(result_string.as_bytes()).unwrap();
+        let retention = &configuration.rule.as_ref().unwrap().default_retention;
+        assert_eq!(retention.mode, "COMPLIANCE");
+        assert_eq!(retention.days, Some(30));
+        assert_eq!(retention.years, None);
+
+        assert_eq!(
+            configuration.to_string(),
+            "<ObjectLockConfiguration xmlns=\"http://s3.amazonaws.com/doc/2006-03-01/\">\
+             <ObjectLockEnabled>Enabled</ObjectLockEnabled>\
+             <Rule><DefaultRetention><Mode>COMPLIANCE</Mode><Days>30</Days></DefaultRetention></Rule>\
+             </ObjectLockConfiguration>"
+        );
+    }
+
+    #[test]
+    fn test_storage_class_round_trip() {
+        for s in &[
+            "STANDARD",
+            "STANDARD_IA",
+            "DEEP_ARCHIVE",
+            "GLACIER_IR",
+            "SNOWBALL",
+        ] {
+            let storage_class: StorageClass = s.parse().unwrap();
+            assert_eq!(&storage_class.to_string(), s);
+        }
+        assert_eq!(
+            "SNOWBALL".parse::<StorageClass>().unwrap(),
+            StorageClass::Unknown("SNOWBALL".to_string())
+        );
+
+        let result_string = r###"<?xml version="1.0" encoding="UTF-8"?>
+            <ListMultipartUploadsResult>
+                <Bucket>rust-s3</Bucket>
+                <IsTruncated>false</IsTruncated>
+                <Upload>
+                    <Initiated>2021-01-01T00:00:00.000Z</Initiated>
+                    <StorageClass>INTELLIGENT_TIERING</StorageClass>
+                    <Key>big.file</Key>
+                    <UploadId>upload-id</UploadId>
+                </Upload>
+            </ListMultipartUploadsResult>
+        "###;
+        let result: ListMultipartUploadsResult =
+            serde_xml::from_reader(result_string.as_bytes()).unwrap();
+        assert_eq!(
+            result.uploads[0].storage_class,
+            StorageClass::IntelligentTiering
+        );
+    }
+
+    #[test]
+    fn test_grant_xml() {
+        let xml = r###"<Grant>
+                <Grantee xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:type="CanonicalUser">
+                    <ID>75aa57f09aa0c8caeab4f8c24e99d10f8e7faeebf76c078efc7c6caea54ba06a</ID>
+                    <DisplayName>ops</DisplayName>
+                </Grantee>
+                <Permission>FULL_CONTROL</Permission>
+            </Grant>"###;
+        let grant: Grant = serde_xml::from_reader(xml.as_bytes()).unwrap();
+        assert_eq!(
+            grant.grantee,
+            Grantee::CanonicalUser {
+                id: "75aa57f09aa0c8caeab4f8c24e99d10f8e7faeebf76c078efc7c6caea54ba06a".to_string(),
+                display_name: Some("ops".to_string()),
+            }
+        );
+        assert_eq!(grant.permission, Permission::FullControl);
+
+        for grant in [
+            grant,
+            Grant {
+                grantee: Grantee::group(Grantee::ALL_USERS),
+                permission: Permission::Read,
+            },
+            Grant {
+                grantee: Grantee::Email("ops@example.com".to_string()),
+                permission: Permission::WriteAcp,
+            },
+        ] {
+            let xml = grant.to_string();
+            assert!(xml.contains("xsi:type="));
+            let parsed: Grant = serde_xml::from_reader(xml.as_bytes()).unwrap();
+            assert_eq!(parsed, grant);
+        }
+        assert_eq!(
+            Grant {
+                grantee: Grantee::group(Grantee::LOG_DELIVERY),
+                permission: Permission::Write,
+            }
+            .to_string(),
+            "<Grant><Grantee xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+             xsi:type=\"Group\"><URI>http://acs.amazonaws.com/groups/s3/LogDelivery</URI>\
+             </Grantee><Permission>WRITE</Permission></Grant>"
+        );
+        assert!("WRITE_ALL".parse::<Permission>().is_err());
+    }
+
+    #[test]
+    fn test_aws_error() {
+        let xml = r###"<?xml version="1.0" encoding="UTF-8"?>
+            <Error>
+                <Code>NoSuchKey</Code>
+                <Message>The specified key does not exist.</Message>
+                <Resource>/rust-s3/test.file</Resource>
+                <RequestId>4442587FB7D0A2F9</RequestId>
+            </Error>"###;
+        let error: AwsError = serde_xml::from_reader(xml.as_bytes()).unwrap();
+        assert_eq!(error.code(), "NoSuchKey");
+        assert_eq!(error.message(), "The specified key does not exist.");
+        assert_eq!(error.request_id(), "4442587FB7D0A2F9");
+        assert_eq!(error.resource(), Some("/rust-s3/test.file"));
+
+        let error: AwsError =
+            serde_xml::from_reader("<Error><Code>SlowDown</Code></Error>".as_bytes()).unwrap();
+        assert_eq!(error.code(), "SlowDown");
+        assert_eq!(error.request_id(), "");
+        assert_eq!(error.resource(), None);
+    }
 }
@@ -1,14 +1,20 @@
 use async_std::io::ReadExt;
+use futures::TryStreamExt;
 use std::io::Write;
 
 use super::bucket::Bucket;
 use super::command::Command;
+use crate::creds::Credentials;
+use crate::error::S3Error;
 use chrono::{DateTime, Utc};
 
 use crate::command::HttpMethod;
-use crate::request_trait::Request;
+use crate::request_trait::{
+    is_not_found, is_region_redirect, is_server_error, region_redirect, temporary_redirect,
+    BodyStream, Request, Retries, MAX_TEMPORARY_REDIRECTS,
+};
 
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use http::HeaderMap;
 use maybe_async::maybe_async;
 use surf::http::headers::{HeaderName, HeaderValue};
@@ -21,6 +27,7 @@ pub struct SurfRequest<'a> {
     pub command: Command<'a>,
     pub datetime: DateTime<Utc>,
     pub sync: bool,
+    pub credentials: Credentials,
 }
 
 #[maybe_async]
@@ -36,6 +43,10 @@ impl<'a> Request for SurfRequest<'a> {
         self.bucket.clone()
     }
 
+    fn credentials(&self) -> Credentials {
+        self.credentials.clone()
+    }
+
     fn command(&self) -> Command {
         self.command.clone()
     }
@@ -45,39 +56,76 @@ impl<'a> Request for SurfRequest<'a> {
     }
 
     async fn response(&self) -> Result<surf::Response> {
-        // Build headers
-        let headers = self.headers()?;
-
-        let request = match self.command.http_verb() {
-            HttpMethod::Get => surf::Request::builder(Method::Get, self.url()),
-            HttpMethod::Delete => surf::Request::builder(Method::Delete, self.url()),
-            HttpMethod::Put => surf::Request::builder(Method::Put, self.url()),
-            HttpMethod::Post => surf::Request::builder(Method::Post, self.url()),
-            HttpMethod::Head => surf::Request::builder(Method::Head, self.url()),
-        };
-
-        let mut request = request.body(self.request_body());
-
-        for (name, value) in headers.iter() {
-            request = request.header(
-                HeaderName::from_bytes(AsRef::<[u8]>::as_ref(&name).to_vec()).unwrap(),
-                HeaderValue::from_bytes(AsRef::<[u8]>::as_ref(&value).to_vec()).unwrap(),
-            );
-        }
-
-        let response = request.send().await.unwrap();
-
-        if cfg!(feature = "fail-on-err") && !response.status().is_success() {
-            return Err(anyhow!("Request failed with code {}", response.status()));
+        self.bucket.refresh_expired_credentials().await?;
+        let mut bucket = self.bucket.clone();
+        let mut redirects = 0;
+        let mut region_redirected = false;
+        let mut retries = Retries::new(self.bucket, &self.command);
+        loop {
+            let result = {
+                let request = SurfRequest::new(&bucket, self.path, self.command.clone());
+                request.send().await
+            };
+            self.bucket
+                .invalidate_head_cache_for(self.path, &self.command);
+            let connection_failed = result.as_ref().err().is_some_and(|e| {
+                e.chain()
+                    .any(|cause| cause.downcast_ref::<std::io::Error>().is_some())
+            });
+            let transient = match &result {
+                Ok(response) => response.status().is_server_error(),
+                Err(e) => is_server_error(e) || connection_failed,
+            };
+            if transient {
+                if let Some(delay) = retries.next_delay() {
+                    async_std::task::sleep(delay).await;
+                    continue;
+                }
+            }
+            let not_yet_created = match &result {
+                Ok(response) => u16::from(response.status()) == 404,
+                Err(e) => is_not_found(e) || connection_failed,
+            };
+            if not_yet_created {
+                if let Some(delay) = retries.next_post_create_delay() {
+                    async_std::task::sleep(delay).await;
+                    continue;
+                }
+            }
+            let response = result?;
+            if !region_redirected {
+                let bucket_region = response
+                    .header("x-amz-bucket-region")
+                    .map(|region| region.as_str());
+                if let Some(redirected) =
+                    region_redirect(&bucket, response.status().into(), bucket_region)?
+                {
+                    bucket = redirected;
+                    region_redirected = true;
+                    continue;
+                }
+            }
+            if redirects < MAX_TEMPORARY_REDIRECTS {
+                let location = response
+                    .header("Location")
+                    .map(|location| location.as_str());
+                if let Some(redirected) =
+                    temporary_redirect(&bucket, &self.command, response.status().into(), location)?
+                {
+                    bucket = redirected;
+                    redirects += 1;
+                    continue;
+                }
+            }
+            return Ok(response);
         }
-
-        Ok(response)
     }
 
     async fn response_data(&self, etag: bool) -> Result<(Vec<u8>, u16)> {
         let mut response = self.response().await?;
         let status_code = response.status();
-        let body = response.body_bytes().await.unwrap();
+        let body = response.body_bytes().await.map_err(|e| e.into_inner())?;
+        self.log_signature_mismatch(status_code.into(), &body);
         let mut body_vec = Vec::new();
         body_vec.extend_from_slice(&body[..]);
         if etag {
@@ -89,40 +137,181 @@ impl<'a> Request for SurfRequest<'a> {
     }
 
     async fn response_data_to_writer<T: Write + Send>(&self, writer: &mut T) -> Result<u16> {
-        let mut buffer = Vec::new();
+        let mut buffer = vec![0; 64 * 1024];
 
-        let response = self.response().await?;
+        let mut response = self.response().await?;
 
         let status_code = response.status();
 
-        let mut stream = surf::http::Body::from_reader(response, None);
+        loop {
+            let n = response.read(&mut buffer).await?;
+            if n == 0 {
+                break;
+            }
+            writer.write_all(&buffer[..n])?;
+        }
 
-        stream.read_to_end(&mut buffer).await?;
+        Ok(status_code.into())
+    }
 
-        writer.write_all(&buffer)?;
+    async fn response_data_to_writer_if_status<T: Write + Send>(
+        &self,
+        writer: &mut T,
+        expected: u16,
+    ) -> Result<(u16, u64)> {
+        let mut response = self.response().await?;
+        let status_code: u16 = response.status().into();
+        if status_code != expected {
+            let body = response.body_bytes().await.map_err(|e| e.into_inner())?;
+            self.log_signature_mismatch(status_code, &body);
+            return Ok((status_code, 0));
+        }
 
-        Ok(status_code.into())
+        let mut buffer = vec![0; 64 * 1024];
+        let mut written = 0;
+        loop {
+            let n = response.read(&mut buffer).await?;
+            if n == 0 {
+                break;
+            }
+            writer.write_all(&buffer[..n])?;
+            written += n as u64;
+        }
+
+        Ok((status_code, written))
+    }
+
+    async fn response_data_streamed(&self, body: BodyStream<'_>) -> Result<(Vec<u8>, u16)> {
+        self.bucket.refresh_expired_credentials().await?;
+        let length = self.command.content_length();
+        let reader = futures::io::BufReader::new(body.into_async_read());
+        let result = self
+            .send_body(surf::Body::from_reader(reader, Some(length)))
+            .await;
+        self.bucket
+            .invalidate_head_cache_for(self.path, &self.command);
+        let mut response = result?;
+        let status_code = response.status();
+        let body = response.body_bytes().await.map_err(|e| e.into_inner())?;
+        self.log_signature_mismatch(status_code.into(), &body);
+        let body = match response.header("ETag") {
+            Some(etag) => etag.as_str().as_bytes().to_vec(),
+            None => body,
+        };
+        Ok((body, status_code.into()))
     }
 
     async fn response_header(&self) -> Result<(HeaderMap, u16)> {
-        let mut header_map = HeaderMap::new();
         let response = self.response().await?;
         let status_code = response.status();
+        Ok((header_map(&response), status_code.into()))
+    }
 
-        for (name, value) in response.iter() {
-            header_map.insert(
-                http::header::HeaderName::from_lowercase(
-                    name.to_string().to_ascii_lowercase().as_ref(),
-                )
-                .unwrap(),
-                value.as_str().parse().unwrap(),
-            );
+    async fn response_data_with_headers(&self) -> Result<(Vec<u8>, HeaderMap, u16)> {
+        let mut response = self.response().await?;
+        let status_code = response.status();
+        let headers = header_map(&response);
+        let body = response.body_bytes().await.map_err(|e| e.into_inner())?;
+        self.log_signature_mismatch(status_code.into(), &body);
+        Ok((body, headers, status_code.into()))
+    }
+
+    async fn response_data_with_max_size(&self, max_body_size: u64) -> Result<(Vec<u8>, u16)> {
+        let response = self.response().await?;
+        let status_code = response.status();
+        if let Some(content_length) = response.len() {
+            if content_length as u64 > max_body_size {
+                return Err(S3Error::BodyTooLarge {
+                    limit: max_body_size,
+                    content_length: Some(content_length as u64),
+                }
+                .into());
+            }
         }
-        Ok((header_map, status_code.into()))
+
+        // One byte past the limit is enough to tell that the body is too large
+        let mut body = Vec::new();
+        response
+            .take(max_body_size + 1)
+            .read_to_end(&mut body)
+            .await?;
+        if body.len() as u64 > max_body_size {
+            return Err(S3Error::BodyTooLarge {
+                limit: max_body_size,
+                content_length: None,
+            }
+            .into());
+        }
+        Ok((body, status_code.into()))
+    }
+}
+
+fn header_map(response: &surf::Response) -> HeaderMap {
+    let mut header_map = HeaderMap::new();
+    for (name, value) in response.iter() {
+        header_map.insert(
+            http::header::HeaderName::from_lowercase(
+                name.to_string().to_ascii_lowercase().as_ref(),
+            )
+            .unwrap(),
+            value.as_str().parse().unwrap(),
+        );
     }
+    header_map
 }
 
 impl<'a> SurfRequest<'a> {
+    async fn send(&self) -> Result<surf::Response> {
+        self.send_body(self.request_body().into()).await
+    }
+
+    async fn send_body(&self, body: surf::Body) -> Result<surf::Response> {
+        // Build headers
+        let (headers, signed) = self.signed_headers()?;
+
+        let request = match self.command.http_verb() {
+            HttpMethod::Get => surf::Request::builder(Method::Get, self.url()),
+            HttpMethod::Delete => surf::Request::builder(Method::Delete, self.url()),
+            HttpMethod::Put => surf::Request::builder(Method::Put, self.url()),
+            HttpMethod::Post => surf::Request::builder(Method::Post, self.url()),
+            HttpMethod::Head => surf::Request::builder(Method::Head, self.url()),
+        };
+
+        let mut request = request.body(body);
+
+        for (name, value) in headers.iter() {
+            request = request.header(
+                HeaderName::from_bytes(AsRef::<[u8]>::as_ref(&name).to_vec()).unwrap(),
+                HeaderValue::from_bytes(AsRef::<[u8]>::as_ref(&value).to_vec()).unwrap(),
+            );
+        }
+
+        let response = match self.bucket.request_timeout() {
+            Some(timeout) => match async_std::future::timeout(timeout, request.send()).await {
+                Ok(response) => response.map_err(|e| e.into_inner()),
+                Err(_) => Err(anyhow::anyhow!("Request timed out after {:?}", timeout)),
+            },
+            None => request.send().await.map_err(|e| e.into_inner()),
+        };
+        self.log_attempt(signed, response.as_ref().ok().map(|r| r.status().into()));
+        let mut response = response?;
+
+        let status: u16 = response.status().into();
+        let redirect = is_region_redirect(
+            self.bucket,
+            status,
+            response
+                .header("x-amz-bucket-region")
+                .map(|region| region.as_str()),
+        );
+        if cfg!(feature = "fail-on-err") && !response.status().is_success() && !redirect {
+            let body = response.body_bytes().await.map_err(|e| e.into_inner())?;
+            return Err(self.error_response(status, &body).into());
+        }
+
+        Ok(response)
+    }
+
     pub fn new<'b>(bucket: &'b Bucket, path: &'b str, command: Command<'b>) -> SurfRequest<'b> {
         SurfRequest {
             bucket,
@@ -130,6 +319,7 @@ impl<'a> SurfRequest<'a> {
             command,
             datetime: Utc::now(),
             sync: false,
+            credentials: bucket.credentials(),
         }
     }
 }
@@ -1,14 +1,15 @@
+use std::collections::HashMap;
 use std::str::FromStr;
 
 use crate::{bucket::CHUNK_SIZE, serde_types::HeadObjectResult};
-use anyhow::Result;
+use anyhow::{Context, Result};
 
 #[cfg(feature = "with-async-std")]
-use async_std::fs::File;
+use async_std::fs::{File, OpenOptions};
 #[cfg(feature = "sync")]
-use std::fs::File;
+use std::fs::{File, OpenOptions};
 #[cfg(feature = "with-tokio")]
-use tokio::fs::File;
+use tokio::fs::{File, OpenOptions};
 
 #[cfg(feature = "with-async-std")]
 use async_std::path::Path;
@@ -16,11 +17,11 @@ use async_std::path::Path;
 use std::path::Path;
 
 #[cfg(feature = "with-async-std")]
-use futures::io::{AsyncRead, AsyncReadExt};
+use futures::io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 #[cfg(feature = "sync")]
-use std::io::Read;
+use std::io::{Read, Seek, Write};
 #[cfg(feature = "with-tokio")]
-use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 
 /// # Example
 /// ```rust,no_run
@@ -104,6 +105,165 @@ pub fn read_chunk<R: Read>(reader: &mut R) -> Result<Vec<u8>> {
 
     Ok(chunk)
 }
+/// Content of the file at `path`, with the path in the error if it cannot be read.
+#[cfg(any(feature = "tokio", feature = "async-std"))]
+pub(crate) async fn read_file(path: &std::path::Path) -> Result<Vec<u8>> {
+    let mut content = Vec::new();
+    File::open(path)
+        .await
+        .with_context(|| format!("Could not open {}", path.display()))?
+        .read_to_end(&mut content)
+        .await
+        .with_context(|| format!("Could not read {}", path.display()))?;
+    Ok(content)
+}
+
+#[cfg(feature = "sync")]
+pub(crate) fn read_file(path: &std::path::Path) -> Result<Vec<u8>> {
+    let mut content = Vec::new();
+    File::open(path)
+        .with_context(|| format!("Could not open {}", path.display()))?
+        .read_to_end(&mut content)
+        .with_context(|| format!("Could not read {}", path.display()))?;
+    Ok(content)
+}
+
+/// Create or truncate the file at `path` and preallocate `len` bytes, to be filled in with
+/// `write_file_at`.
+#[cfg(any(feature = "tokio", feature = "async-std"))]
+pub(crate) async fn create_file_with_len(path: &std::path::Path, len: u64) -> Result<()> {
+    File::create(path)
+        .await
+        .with_context(|| format!("Could not create {}", path.display()))?
+        .set_len(len)
+        .await
+        .with_context(|| format!("Could not allocate {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(feature = "sync")]
+pub(crate) fn create_file_with_len(path: &std::path::Path, len: u64) -> Result<()> {
+    File::create(path)
+        .with_context(|| format!("Could not create {}", path.display()))?
+        .set_len(len)
+        .with_context(|| format!("Could not allocate {}", path.display()))?;
+    Ok(())
+}
+
+/// Write `data` into the existing file at `path`, starting at `offset`.
+#[cfg(any(feature = "tokio", feature = "async-std"))]
+pub(crate) async fn write_file_at(path: &std::path::Path, offset: u64, data: &[u8]) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .open(path)
+        .await
+        .with_context(|| format!("Could not open {}", path.display()))?;
+    file.seek(std::io::SeekFrom::Start(offset)).await?;
+    file.write_all(data)
+        .await
+        .with_context(|| format!("Could not write {}", path.display()))?;
+    // Pending writes of an async file are only done once flushed
+    file.flush().await?;
+    Ok(())
+}
+
+#[cfg(feature = "sync")]
+pub(crate) fn write_file_at(path: &std::path::Path, offset: u64, data: &[u8]) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .open(path)
+        .with_context(|| format!("Could not open {}", path.display()))?;
+    file.seek(std::io::SeekFrom::Start(offset))?;
+    file.write_all(data)
+        .with_context(|| format!("Could not write {}", path.display()))?;
+    Ok(())
+}
+
+/// Content type for a file, guessed from the extension of `path`, `application/octet-stream`
+/// for unknown extensions.
+pub fn content_type_for_path(path: impl AsRef<std::path::Path>) -> &'static str {
+    let extension = path
+        .as_ref()
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("html") | Some("htm") => "text/html",
+        Some("css") => "text/css",
+        Some("csv") => "text/csv",
+        Some("txt") | Some("log") => "text/plain",
+        Some("md") => "text/markdown",
+        Some("js") | Some("mjs") => "text/javascript",
+        Some("json") => "application/json",
+        Some("xml") => "application/xml",
+        Some("pdf") => "application/pdf",
+        Some("zip") => "application/zip",
+        Some("gz") => "application/gzip",
+        Some("tar") => "application/x-tar",
+        Some("wasm") => "application/wasm",
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("svg") => "image/svg+xml",
+        Some("webp") => "image/webp",
+        Some("ico") => "image/vnd.microsoft.icon",
+        Some("mp3") => "audio/mpeg",
+        Some("mp4") => "video/mp4",
+        Some("webm") => "video/webm",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Bitwise CRC-32 with the reflected polynomial `poly`, small inputs only need no table.
+/// `crc`, the CRC of the data so far, `0` for none, updated with `data`.
+fn crc32_update_with_poly(poly: u32, crc: u32, data: &[u8]) -> u32 {
+    let mut crc = !crc;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ poly
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// CRC-32 (IEEE), as used by event stream messages and `x-amz-checksum-crc32`.
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    crc32_update(0, data)
+}
+
+/// `crc32` of the data so far, `crc`, followed by `data`.
+pub(crate) fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+    crc32_update_with_poly(0xEDB8_8320, crc, data)
+}
+
+/// CRC-32C (Castagnoli), as used by `x-amz-checksum-crc32c`, of the data so far, `crc`, `0`
+/// for none, followed by `data`.
+pub(crate) fn crc32c_update(crc: u32, data: &[u8]) -> u32 {
+    crc32_update_with_poly(0x82F6_3B78, crc, data)
+}
+
+/// Headers as a map from lowercase names to values, repeated headers are joined with `, `,
+/// values that are not valid UTF-8 are skipped.
+pub(crate) fn header_map_to_hashmap(headers: &http::HeaderMap) -> HashMap<String, String> {
+    let mut map: HashMap<String, String> = HashMap::new();
+    for (name, value) in headers.iter() {
+        if let Ok(value) = value.to_str() {
+            map.entry(name.as_str().to_lowercase())
+                .and_modify(|joined| {
+                    joined.push_str(", ");
+                    joined.push_str(value);
+                })
+                .or_insert_with(|| value.to_string());
+        }
+    }
+    map
+}
+
 pub trait GetAndConvertHeaders {
     fn get_and_convert<T: FromStr>(&self, header: &str) -> Option<T>;
     fn get_string(&self, header: &str) -> Option<String>;
@@ -126,13 +286,18 @@ impl From<&http::HeaderMap> for HeadObjectResult {
             content_disposition: headers.get_string("Content-Disposition"),
             content_encoding: headers.get_string("Content-Encoding"),
             content_language: headers.get_string("Content-Language"),
-            content_length: headers.get_and_convert("Content-Length"),
+            content_length: headers
+                .get_and_convert("Content-Length")
+                .unwrap_or_default(),
             content_type: headers.get_string("Content-Type"),
             delete_marker: headers.get_and_convert("x-amz-delete-marker"),
-            e_tag: headers.get_string("ETag"),
+            etag: headers.get_string("ETag"),
             expiration: headers.get_string("x-amz-expiration"),
             expires: headers.get_string("Expires"),
-            last_modified: headers.get_string("Last-Modified"),
+            last_modified: headers
+                .get_string("Last-Modified")
+                .as_deref()
+                .and_then(crate::deserializer::parse_http_date),
             ..Default::default()
         };
         let mut values = ::std::collections::HashMap::new();
@@ -146,7 +311,7 @@ impl From<&http::HeaderMap> for HeadObjectResult {
                 }
             }
         }
-        result.metadata = Some(values);
+        result.metadata = values;
         result.missing_meta = headers.get_and_convert("x-amz-missing-meta");
         result.object_lock_legal_hold_status = headers.get_string("x-amz-object-lock-legal-hold");
         result.object_lock_mode = headers.get_string("x-amz-object-lock-mode");
@@ -162,7 +327,7 @@ impl From<&http::HeaderMap> for HeadObjectResult {
             headers.get_string("x-amz-server-side-encryption-customer-key-MD5");
         result.ssekms_key_id = headers.get_string("x-amz-server-side-encryption-aws-kms-key-id");
         result.server_side_encryption = headers.get_string("x-amz-server-side-encryption");
-        result.storage_class = headers.get_string("x-amz-storage-class");
+        result.storage_class = headers.get_and_convert("x-amz-storage-class");
         result.version_id = headers.get_string("x-amz-version-id");
         result.website_redirect_location = headers.get_string("x-amz-website-redirect-location");
         result
@@ -171,9 +336,11 @@ impl From<&http::HeaderMap> for HeadObjectResult {
 
 #[cfg(test)]
 mod test {
-    use crate::utils::etag_for_path;
+    use crate::serde_types::{HeadObjectResult, StorageClass};
+    use crate::utils::{content_type_for_path, crc32, crc32_update, crc32c_update, etag_for_path};
     #[cfg(feature = "with-async-std")]
     use async_std::io::Cursor;
+    use chrono::{TimeZone, Utc};
     use std::fs::File;
     use std::io::prelude::*;
     #[cfg(any(feature = "with-tokio", feature = "sync"))]
@@ -183,6 +350,60 @@ mod test {
         (0..size).map(|_| 33).collect()
     }
 
+    #[test]
+    fn test_content_type_for_path() {
+        assert_eq!(content_type_for_path("site/index.html"), "text/html");
+        assert_eq!(content_type_for_path("photos/IMG_0001.JPG"), "image/jpeg");
+        assert_eq!(content_type_for_path("data.json"), "application/json");
+        assert_eq!(content_type_for_path("archive.tar.gz"), "application/gzip");
+        assert_eq!(
+            content_type_for_path("data.parquet"),
+            "application/octet-stream"
+        );
+        assert_eq!(content_type_for_path("README"), "application/octet-stream");
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32c_update(0, b"123456789"), 0xE306_9283);
+        assert_eq!(crc32_update(crc32(b"1234"), b"56789"), 0xCBF4_3926);
+        assert_eq!(
+            crc32c_update(crc32c_update(0, b"1234"), b"56789"),
+            0xE306_9283
+        );
+    }
+
+    #[test]
+    fn test_head_object_result_from_headers() {
+        let mut headers = http::HeaderMap::new();
+        for (key, value) in &[
+            ("content-length", "3072"),
+            ("content-type", "text/plain"),
+            ("etag", "\"abc\""),
+            ("last-modified", "Wed, 21 Oct 2015 07:28:00 GMT"),
+            ("cache-control", "max-age=60"),
+            ("x-amz-storage-class", "STANDARD_IA"),
+            ("x-amz-meta-owner", "data-team"),
+        ] {
+            headers.insert(*key, value.parse().unwrap());
+        }
+        let result = HeadObjectResult::from(&headers);
+        assert_eq!(result.content_length, 3072);
+        assert_eq!(result.content_type.as_deref(), Some("text/plain"));
+        assert_eq!(result.etag.as_deref(), Some("\"abc\""));
+        assert_eq!(
+            result.last_modified,
+            Some(Utc.ymd(2015, 10, 21).and_hms(7, 28, 0))
+        );
+        assert_eq!(result.cache_control.as_deref(), Some("max-age=60"));
+        assert_eq!(result.storage_class, Some(StorageClass::StandardIa));
+        assert_eq!(
+            result.metadata.get("owner").map(String::as_str),
+            Some("data-team")
+        );
+    }
+
     #[maybe_async::test(
         feature = "sync",
         async(all(not(feature = "sync"), feature = "with-tokio"), tokio::test),